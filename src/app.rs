@@ -1,19 +1,31 @@
 use std::{
-    fs::File,
-    io::BufReader,
+    env, fmt, io, mem,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
     time::{Duration, Instant},
 };
 
-use crate::pomodoro_technique::{Cycle, Interruption, Stage};
+use crate::config::{Config, GitConfig, LongPauseConfig, PreEndWarningConfig, TimerConfig};
+use crate::control::{Client, ControlCommand, Response, Snapshot, Status};
+use crate::git::{self, Commit};
+use crate::history::{CycleRecord, HistoryStore};
+use crate::notifier::{Event, Notifiers};
+use crate::pomodoro_technique::{wall_clock, Cycle, Interruption, Stage};
+use crate::widgets::{TextInput, CHARTS_WINDOW_DAYS};
 
-use rodio::Source;
-use wsl;
+use chrono::{Local, NaiveDate};
+use crossterm::event::KeyEvent;
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AppConfiguration {
+    #[serde(with = "humantime_serde")]
     work_duration: Duration,
+    #[serde(with = "humantime_serde")]
     short_break_duration: Duration,
+    #[serde(with = "humantime_serde")]
     long_break_duration: Duration,
-    stage_sequence: [Stage; 8],
+    stage_sequence: Vec<Stage>,
 }
 
 impl Default for AppConfiguration {
@@ -22,8 +34,7 @@ impl Default for AppConfiguration {
             work_duration: Duration::new(1500, 0),
             short_break_duration: Duration::new(300, 0),
             long_break_duration: Duration::new(1200, 0),
-            // TODO: make sequence configurable
-            stage_sequence: [
+            stage_sequence: vec![
                 Stage::Work,
                 Stage::ShortBreak,
                 Stage::Work,
@@ -37,10 +48,81 @@ impl Default for AppConfiguration {
     }
 }
 
+impl AppConfiguration {
+    /// The built-in plan, with whatever `timer` sets in place of the defaults.
+    pub fn from_timer_config(timer: &TimerConfig) -> AppConfiguration {
+        let default = AppConfiguration::default();
+
+        AppConfiguration {
+            work_duration: timer.work.unwrap_or(default.work_duration),
+            short_break_duration: timer.short_break.unwrap_or(default.short_break_duration),
+            long_break_duration: timer.long_break.unwrap_or(default.long_break_duration),
+            stage_sequence: timer.sequence.clone().unwrap_or(default.stage_sequence),
+        }
+    }
+
+    fn get_stage_duration(&self, stage: &Stage) -> Duration {
+        match stage {
+            Stage::Work => self.work_duration,
+            Stage::ShortBreak => self.short_break_duration,
+            Stage::LongBreak => self.long_break_duration,
+        }
+    }
+}
+
+/// The plan of one round of stages, one per line, with its total length.
+impl fmt::Display for AppConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, stage) in self.stage_sequence.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>2}. {:<12} {}",
+                idx + 1,
+                stage.to_string(),
+                humantime::format_duration(self.get_stage_duration(stage))
+            )?;
+        }
+
+        let pomodoros = self
+            .stage_sequence
+            .iter()
+            .filter(|stage| **stage == Stage::Work)
+            .count();
+        let total: Duration = self
+            .stage_sequence
+            .iter()
+            .map(|stage| self.get_stage_duration(stage))
+            .sum();
+
+        write!(
+            f,
+            "One round: {} pomodoro{} in {}, then it starts over",
+            pomodoros,
+            if pomodoros == 1 { "" } else { "s" },
+            humantime::format_duration(total)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AppView {
     Normal,
     AnnotationPopup,
+    TaskPrompt,
     InterruptionsList,
+    History,
+    Charts,
+    Heatmap,
+}
+
+/// The commits of a finished work cycle, being looked up in the background.
+struct CommitLookup {
+    /// Index of the cycle in the history
+    cycle: usize,
+    /// Index of its record, which is stored once the commits are in
+    record: Option<usize>,
+    commits: Receiver<Vec<Commit>>,
 }
 
 pub struct App {
@@ -48,34 +130,231 @@ pub struct App {
     current_view: AppView,
     current_cycle: Cycle,
     history: Vec<Cycle>,
+    selected_interruption: Option<usize>,
+    annotation_input: TextInput,
+    task: Option<String>,
+    task_input: TextInput,
+    selected_cycle: Option<usize>,
+    /// Index into today's history of the cycle whose interruptions are listed
+    inspected_cycle: Option<usize>,
+    records: Vec<CycleRecord>,
+    history_store: Option<HistoryStore>,
+    /// How many windows back from today the charts view is showing
+    charts_window: u32,
+    notifiers: Notifiers,
+    /// Whether the timer was running as of the last `TimerRunning` event
+    timer_running: bool,
+    daily_goal: u32,
+    long_pause: LongPauseConfig,
+    /// How many times the ongoing pause has been reminded about
+    long_pause_reminders: u64,
+    pre_end_warning: PreEndWarningConfig,
+    /// Whether the current cycle has already been warned about ending
+    pre_end_warned: bool,
+    /// Where to look for the commits made during work stages, if anywhere
+    git: GitConfig,
+    commit_lookups: Vec<CommitLookup>,
+    /// The instance owning the timer, when attached to one
+    owner: Option<Client>,
+    /// The status of the owner as of the last state mirrored from it
+    owner_status: Option<Status>,
 }
 
 impl Default for App {
     fn default() -> App {
+        let config = AppConfiguration::default();
+        let first_stage = config.stage_sequence[0];
+
         App {
             current_view: AppView::Normal,
-            config: AppConfiguration::default(),
-            current_cycle: Cycle {
-                stage_iteration: 0,
-                started_at: None,
-                finished_at: None,
-                interruption_history: Vec::new(),
-                interruption: None,
-            },
+            config,
+            current_cycle: Cycle::new(0, first_stage),
             history: Vec::new(),
+            selected_interruption: None,
+            annotation_input: TextInput::default(),
+            task: None,
+            task_input: TextInput::default(),
+            selected_cycle: None,
+            inspected_cycle: None,
+            records: Vec::new(),
+            history_store: None,
+            charts_window: 0,
+            notifiers: Notifiers::default(),
+            timer_running: false,
+            daily_goal: 0,
+            long_pause: LongPauseConfig::default(),
+            long_pause_reminders: 0,
+            pre_end_warning: PreEndWarningConfig::default(),
+            pre_end_warned: false,
+            git: GitConfig::default(),
+            commit_lookups: Vec::new(),
+            owner: None,
+            owner_status: None,
         }
     }
 }
 
 impl App {
+    /// Creates an app set up by `config`, which records finished cycles in `store`
+    /// starting from the cycles already persisted there, and tells `notifiers` about its events.
+    pub fn new(
+        config: Config,
+        store: Option<HistoryStore>,
+        notifiers: Notifiers,
+    ) -> io::Result<App> {
+        let records = match store.as_ref() {
+            Some(store) => store.load()?,
+            None => Vec::new(),
+        };
+        let timer = AppConfiguration::from_timer_config(&config.timer);
+        let mut git = config.git;
+        if git.enabled && git.repositories.is_empty() {
+            git.repositories.extend(env::current_dir());
+        }
+        let first_stage = timer.stage_sequence[0];
+
+        Ok(App {
+            config: timer,
+            current_cycle: Cycle::new(0, first_stage),
+            records,
+            history_store: store,
+            notifiers,
+            daily_goal: config.daily_goal,
+            long_pause: config.long_pause,
+            pre_end_warning: config.pre_end_warning,
+            git,
+            ..App::default()
+        })
+    }
+
+    /// Hands the timer over to the instance `owner` is connected to:
+    /// commands are sent to it, and its state is mirrored whenever it changes.
+    /// Should it exit, the app carries on with the last state it mirrored.
+    pub fn attach(&mut self, owner: Client) {
+        self.owner = Some(owner);
+        self.send_to_owner(ControlCommand::Snapshot);
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.owner.is_some()
+    }
+
+    fn send_to_owner(&mut self, command: ControlCommand) {
+        let owner = match self.owner.as_mut() {
+            Some(owner) => owner,
+            None => return,
+        };
+
+        // Commands which can't run, e.g. pausing an already paused timer, are simply ignored
+        let response = match command {
+            ControlCommand::Snapshot => owner.send(command),
+            _ => owner
+                .send(command)
+                .and_then(|_| owner.send(ControlCommand::Snapshot)),
+        };
+
+        match response {
+            Ok(Response {
+                snapshot: Some(snapshot),
+                status,
+                ..
+            }) => {
+                self.owner_status = status;
+                self.apply_snapshot(snapshot);
+            }
+            Ok(_) => {}
+            Err(_) => self.owner = None,
+        }
+    }
+
+    /// Asks the owner for its status, which is cheap, and only for the whole
+    /// of its state, which grows with the history, once the status has changed.
+    fn mirror_owner(&mut self) {
+        let owner = match self.owner.as_mut() {
+            Some(owner) => owner,
+            None => return,
+        };
+
+        let status = match owner.send(ControlCommand::Status) {
+            Ok(Response {
+                status: Some(status),
+                ..
+            }) => status,
+            Ok(_) => return,
+            Err(_) => {
+                self.owner = None;
+                return;
+            }
+        };
+
+        let changed = match self.owner_status.as_ref() {
+            Some(last_status) => status.has_changed_state(last_status),
+            None => true,
+        };
+        if changed {
+            self.send_to_owner(ControlCommand::Snapshot);
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            current_cycle: self.current_cycle.clone(),
+            history: self.history.clone(),
+            task: self.task.clone(),
+            timer: self.config.clone(),
+        }
+    }
+
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        let commits =
+            |history: &[Cycle]| -> usize { history.iter().map(|cycle| cycle.commits.len()).sum() };
+        // The owner has finished a cycle, or found its commits, and recorded it
+        if snapshot.history.len() != self.history.len()
+            || commits(&snapshot.history) != commits(&self.history)
+        {
+            if let Some(store) = self.history_store.as_ref() {
+                self.records = store.load().unwrap_or_else(|_| self.records.clone());
+            }
+        }
+
+        self.current_cycle = snapshot.current_cycle;
+        self.history = snapshot.history;
+        self.task = snapshot.task;
+        self.config = snapshot.timer;
+
+        if let (AppView::AnnotationPopup, false) = (&self.current_view, self.is_paused()) {
+            self.set_view(AppView::Normal);
+        }
+    }
+
     pub fn change_view(&mut self, view: AppView) {
-        self.current_view = view
+        match view {
+            AppView::Charts => self.charts_window = 0,
+            AppView::InterruptionsList => self.selected_interruption = None,
+            AppView::TaskPrompt => self
+                .task_input
+                .set_value(self.task.as_deref().unwrap_or("")),
+            AppView::Normal => self.inspected_cycle = None,
+            _ => {}
+        }
+        self.set_view(view);
+    }
+
+    fn set_view(&mut self, view: AppView) {
+        if self.current_view != view {
+            self.current_view = view;
+            self.notifiers.notify(&Event::ViewChanged { view });
+        }
     }
 
     pub fn get_view(&self) -> &AppView {
         &self.current_view
     }
 
+    pub fn is_started(&self) -> bool {
+        self.current_cycle.started_at.is_some()
+    }
+
     pub fn is_paused(&self) -> bool {
         self.current_cycle.interruption.is_some()
     }
@@ -87,33 +366,26 @@ impl App {
         }
     }
 
-    pub fn append_to_interruption_annotation(&mut self, c: char) {
-        self.current_cycle
-            .interruption
-            .as_mut()
-            .and_then(|interruption| match interruption.annotation.as_mut() {
-                Some(annotation) => {
-                    annotation.push(c);
-                    Some(())
-                }
-                None => {
-                    interruption.annotation = Some(c.to_string());
-                    Some(())
-                }
-            });
+    /// Seconds of pause after which the pause timer should warn and alert.
+    pub fn get_long_pause_thresholds(&self) -> (u64, u64) {
+        (
+            self.long_pause.warn_after * 60,
+            self.long_pause.remind_after * 60,
+        )
     }
 
-    pub fn pop_from_interruption_annotation(&mut self) {
-        self.current_cycle
-            .interruption
-            .as_mut()
-            .and_then(|interruption| match interruption.annotation.as_mut() {
-                Some(annotation) => {
-                    annotation.pop();
-                    Some(())
-                }
-                None => None,
-            });
+    fn is_long_pause_reminder_due(&self) -> bool {
+        if !self.is_paused() {
+            return false;
+        }
+
+        let reminded = self.long_pause_reminders;
+        if reminded > 0 && self.long_pause.remind_every == 0 {
+            return false;
+        }
+
+        let next_reminder = self.long_pause.remind_after + reminded * self.long_pause.remind_every;
+        self.get_pause_elapsed_time() >= next_reminder * 60
     }
 
     pub fn get_interruption_annotation(&self) -> Option<String> {
@@ -123,13 +395,272 @@ impl App {
         }
     }
 
-    pub fn get_interruption_history(&self) -> &Vec<Interruption> {
-        &self.current_cycle.interruption_history
+    pub fn get_annotation_input(&self) -> &TextInput {
+        &self.annotation_input
+    }
+
+    /// Applies a key press to the annotation of the ongoing interruption.
+    pub fn edit_interruption_annotation(&mut self, key: KeyEvent) {
+        if self.annotation_input.handle_key(key) {
+            self.save_interruption_annotation();
+        }
+    }
+
+    /// Sets the annotation of the ongoing interruption, if the timer is paused.
+    pub fn annotate_interruption(&mut self, text: &str) -> bool {
+        if !self.is_paused() {
+            return false;
+        }
+
+        self.annotation_input.set_value(text);
+        self.save_interruption_annotation();
+        true
+    }
+
+    /// Closes the annotation popup. When attached, this is when the annotation
+    /// is sent to the owner, rather than on every key press.
+    pub fn submit_interruption_annotation(&mut self) {
+        if let Some(owner) = self.owner.as_mut() {
+            let text = self.annotation_input.value().to_string();
+            match owner.send(ControlCommand::Annotate { text: text.clone() }) {
+                // Mirrored right away, as the status the owner is polled for doesn't include it
+                Ok(Response { error: None, .. }) => {
+                    if let Some(interruption) = self.current_cycle.interruption.as_mut() {
+                        interruption.annotation = Some(text).filter(|text| !text.is_empty());
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => self.owner = None,
+            }
+        }
+
+        self.change_view(AppView::Normal);
+    }
+
+    fn save_interruption_annotation(&mut self) {
+        // Sent once submitted
+        if self.is_attached() {
+            return;
+        }
+
+        let annotation = match self.annotation_input.is_empty() {
+            true => None,
+            false => Some(self.annotation_input.value().to_string()),
+        };
+
+        if let Some(interruption) = self.current_cycle.interruption.as_mut() {
+            if interruption.annotation != annotation {
+                interruption.annotation = annotation.clone();
+                self.notifiers
+                    .notify(&Event::AnnotationUpdated { annotation });
+            }
+        }
+    }
+
+    /// Inserts pasted text into the prompt being shown; elsewhere, it is ignored.
+    pub fn paste(&mut self, text: &str) {
+        match self.current_view {
+            AppView::AnnotationPopup => {
+                self.annotation_input.paste(text);
+                self.save_interruption_annotation();
+            }
+            AppView::TaskPrompt => self.task_input.paste(text),
+            _ => {}
+        }
+    }
+
+    pub fn get_task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    pub fn get_task_input(&self) -> &TextInput {
+        &self.task_input
+    }
+
+    pub fn edit_task(&mut self, key: KeyEvent) {
+        self.task_input.handle_key(key);
+    }
+
+    /// Sets the task being worked on from the task prompt.
+    /// It is recorded on every cycle finished from now on.
+    pub fn save_task(&mut self) {
+        let task = match self.task_input.is_empty() {
+            true => None,
+            false => Some(self.task_input.value().trim().to_string()),
+        };
+
+        match self.is_attached() {
+            true => self.send_to_owner(ControlCommand::SetTask { task }),
+            false => self.set_task(task),
+        }
+    }
+
+    pub fn set_task(&mut self, task: Option<String>) {
+        self.task = task;
+        self.current_cycle.task = self.task.clone();
+    }
+
+    /// Interruptions shown by the interruptions list: those of the cycle
+    /// inspected from the history view, or else those of the current cycle.
+    pub fn get_interruption_history(&self) -> &[Interruption] {
+        match self.inspected_cycle {
+            Some(idx) => self
+                .get_todays_history()
+                .get(idx)
+                .map_or(&[], |cycle| &cycle.interruption_history),
+            None => &self.current_cycle.interruption_history,
+        }
+    }
+
+    pub fn is_inspecting_cycle(&self) -> bool {
+        self.inspected_cycle.is_some()
+    }
+
+    /// Leaves the interruptions list, going back to wherever it was opened from.
+    pub fn close_interruptions_list(&mut self) {
+        match self.inspected_cycle.take() {
+            Some(_) => self.set_view(AppView::History),
+            None => self.change_view(AppView::Normal),
+        }
+    }
+
+    /// Cycles which were started today, oldest first.
+    pub fn get_todays_history(&self) -> Vec<&Cycle> {
+        let today = Local::now().date_naive();
+
+        self.history
+            .iter()
+            .filter(|cycle| match cycle.started_at {
+                Some(started_at) => wall_clock(started_at).date_naive() == today,
+                None => false,
+            })
+            .collect()
+    }
+
+    pub fn get_selected_cycle(&self) -> Option<usize> {
+        self.selected_cycle
+    }
+
+    pub fn select_next_cycle(&mut self) {
+        let last = match self.get_todays_history().len() {
+            0 => return,
+            len => len - 1,
+        };
+
+        self.selected_cycle = Some(match self.selected_cycle {
+            Some(idx) => (idx + 1).min(last),
+            None => 0,
+        });
+    }
+
+    /// Drops selections which no longer point into today's history,
+    /// as happens once it gets emptied at midnight.
+    fn forget_stale_cycle_selection(&mut self) {
+        let len = self.get_todays_history().len();
+
+        if matches!(self.inspected_cycle, Some(idx) if idx >= len) {
+            self.close_interruptions_list();
+        }
+
+        self.selected_cycle = match len {
+            0 => None,
+            len => self.selected_cycle.map(|idx| idx.min(len - 1)),
+        };
+    }
+
+    pub fn select_previous_cycle(&mut self) {
+        if self.get_todays_history().is_empty() {
+            return;
+        }
+
+        self.selected_cycle = Some(match self.selected_cycle {
+            Some(idx) => idx.saturating_sub(1),
+            None => 0,
+        });
+    }
+
+    /// Finished cycles of every session, oldest first.
+    pub fn get_records(&self) -> &[CycleRecord] {
+        &self.records
+    }
+
+    /// Pomodoros completed since midnight.
+    pub fn get_todays_pomodoro_count(&self) -> usize {
+        let today = Local::now().date_naive();
+
+        self.records
+            .iter()
+            .filter(|record| record.is_pomodoro() && record.started_at.date_naive() == today)
+            .count()
+    }
+
+    /// Last day of the window of days covered by the charts view.
+    pub fn get_charts_last_day(&self) -> NaiveDate {
+        let days_back = self.charts_window * CHARTS_WINDOW_DAYS;
+        Local::now().date_naive() - chrono::Duration::days(days_back as i64)
+    }
+
+    pub fn show_earlier_charts(&mut self) {
+        self.charts_window += 1;
+    }
+
+    pub fn show_later_charts(&mut self) {
+        self.charts_window = self.charts_window.saturating_sub(1);
+    }
+
+    /// Opens the interruptions list for the cycle selected in the history view.
+    pub fn inspect_selected_cycle(&mut self) {
+        if let Some(idx) = self.selected_cycle {
+            self.change_view(AppView::InterruptionsList);
+            self.inspected_cycle = Some(idx);
+        }
+    }
+
+    pub fn get_selected_interruption(&self) -> Option<usize> {
+        self.selected_interruption
+    }
+
+    pub fn select_next_interruption(&mut self) {
+        let last = match self.get_interruption_history().len() {
+            0 => return,
+            len => len - 1,
+        };
+
+        self.selected_interruption = Some(match self.selected_interruption {
+            Some(idx) => (idx + 1).min(last),
+            None => 0,
+        });
+    }
+
+    pub fn select_previous_interruption(&mut self) {
+        if self.get_interruption_history().is_empty() {
+            return;
+        }
+
+        self.selected_interruption = Some(match self.selected_interruption {
+            Some(idx) => idx.saturating_sub(1),
+            None => 0,
+        });
+    }
+
+    /// How many cycles came before the current one since the timer started.
+    pub fn get_cycle_number(&self) -> usize {
+        self.current_cycle.stage_iteration
+    }
+
+    /// Number of interruptions of the current cycle, including an ongoing one.
+    pub fn get_interruption_count(&self) -> usize {
+        self.current_cycle.interruption_history.len()
+            + self.current_cycle.interruption.iter().count()
     }
 
     pub fn get_current_stage(&self) -> &Stage {
-        let idx = self.current_cycle.stage_iteration % self.config.stage_sequence.len();
-        &self.config.stage_sequence[idx]
+        &self.current_cycle.stage
+    }
+
+    fn get_stage_at(&self, stage_iteration: usize) -> Stage {
+        let idx = stage_iteration % self.config.stage_sequence.len();
+        self.config.stage_sequence[idx]
     }
 
     /// Toggles the timer.
@@ -140,21 +671,69 @@ impl App {
     /// - Pause the timer if it is currently running
     /// - Resume the timer if it is currently paused
     pub fn toggle_timer(&mut self) {
+        if self.is_attached() {
+            self.send_to_owner(ControlCommand::Toggle);
+            return;
+        }
+
         let toggled_at = Instant::now();
 
+        let stage = *self.get_current_stage();
+
         if self.current_cycle.started_at.is_none() {
             self.current_cycle.started_at = Some(toggled_at);
+            self.notifiers.notify(&Event::StageStarted {
+                stage,
+                duration: self.get_stage_duration(&stage),
+            });
             return;
         }
 
         if self.current_cycle.interruption.is_none() {
             self.current_cycle.interruption = Some(Interruption::new(toggled_at));
-            self.current_view = AppView::AnnotationPopup;
+            self.long_pause_reminders = 0;
+            self.notifiers.notify(&Event::PauseStarted {
+                stage,
+                remaining: self.get_remaining_duration(),
+            });
         } else {
             let mut interruption = self.current_cycle.interruption.take().unwrap();
             interruption.finished_at = Some(toggled_at);
-            self.current_cycle.interruption_history.push(interruption);
+            let paused_for = toggled_at - interruption.started_at;
+            // The remaining time only leaves out the pause once it is in the history
+            self.finish_interruption(interruption);
+            self.notifiers.notify(&Event::Resumed {
+                stage,
+                remaining: self.get_remaining_duration(),
+                paused_for,
+            });
+        }
+    }
+
+    /// Toggles the timer on behalf of the user in front of this instance,
+    /// who is asked what interrupted them when it pauses.
+    /// Toggling it from elsewhere, e.g. with `pomodoro toggle`, leaves the view alone.
+    pub fn toggle_timer_and_annotate(&mut self) {
+        let was_paused = self.is_paused();
+        self.toggle_timer();
+
+        if self.is_paused() && !was_paused {
+            let annotation = self.get_interruption_annotation().unwrap_or_default();
+            self.annotation_input.set_value(&annotation);
+            self.set_view(AppView::AnnotationPopup);
+        }
+    }
+
+    /// Records an interruption which is over, and tells about its annotation, if any.
+    fn finish_interruption(&mut self, interruption: Interruption) {
+        if let Some(annotation) = interruption.annotation.clone() {
+            self.notifiers.notify(&Event::InterruptionAnnotated {
+                stage: self.current_cycle.stage,
+                annotation,
+            });
         }
+
+        self.current_cycle.interruption_history.push(interruption);
     }
 
     /// Calculates the elapsed duration of the current pomodoro stage.
@@ -179,17 +758,7 @@ impl App {
             return Instant::now() - started_at;
         }
 
-        let total_elapsed_on_pauses: Duration = self
-            .current_cycle
-            .interruption_history
-            .iter()
-            .fold(Duration::new(0, 0), |total, interruption| {
-                let elapsed = match interruption.finished_at {
-                    Some(finished_at) => (finished_at - interruption.started_at),
-                    None => Duration::new(0, 0),
-                };
-                total + elapsed
-            });
+        let total_elapsed_on_pauses = self.current_cycle.paused_time();
 
         let was_last_active_at = match self.current_cycle.interruption.as_ref() {
             Some(interruption) => interruption.started_at,
@@ -207,23 +776,14 @@ impl App {
     /// let (is_due, remaining_time) = app.get_remaining_time();
     /// ```
     ///
-    pub fn get_remaining_time(&mut self) -> (bool, String) {
-        let elapsed = self.get_elapsed_time();
-
-        let duration = match self.get_current_stage() {
-            Stage::Work => self.config.work_duration,
-            Stage::ShortBreak => self.config.short_break_duration,
-            Stage::LongBreak => self.config.long_break_duration,
-        };
+    pub fn get_remaining_time(&self) -> (bool, String) {
+        let remaining = self.get_remaining_duration();
 
-        if elapsed >= duration {
-            if self.current_cycle.finished_at.is_none() {
-                self.current_cycle.finished_at = Some(Instant::now());
-            }
+        if remaining == Duration::new(0, 0) {
             return (true, String::from("00:00"));
         }
 
-        let remaining = (duration - elapsed).as_secs();
+        let remaining = remaining.as_secs();
 
         let minutes = remaining / 60;
         let seconds = remaining % 60;
@@ -231,25 +791,385 @@ impl App {
         (false, format!("{:02}:{:02}", minutes, seconds))
     }
 
+    /// Time left in the current stage, not counting pauses; zero once it is due.
+    pub fn get_remaining_duration(&self) -> Duration {
+        let duration = self.get_stage_duration(self.get_current_stage());
+
+        duration
+            .checked_sub(self.get_elapsed_time())
+            .unwrap_or_default()
+    }
+
+    /// Whether the current stage is running and within its pre-end warning.
+    pub fn is_stage_ending(&self) -> bool {
+        let warning = self.get_pre_end_warning(self.get_current_stage());
+
+        self.current_cycle.started_at.is_some()
+            && warning > Duration::new(0, 0)
+            && self.get_remaining_duration().as_secs() <= warning.as_secs()
+    }
+
+    fn get_pre_end_warning(&self, stage: &Stage) -> Duration {
+        let secs = match stage {
+            Stage::Work => self.pre_end_warning.work,
+            Stage::ShortBreak => self.pre_end_warning.short_break,
+            Stage::LongBreak => self.pre_end_warning.long_break,
+        };
+
+        Duration::from_secs(secs)
+    }
+
+    pub fn get_stage_duration(&self, stage: &Stage) -> Duration {
+        self.config.get_stage_duration(stage)
+    }
+
+    /// Advances the app state; meant to be called once per tick of the main loop.
+    /// Finishes the current cycle as soon as its timer is due, warns when it is about to,
+    /// reminds of pauses running for too long and tells whether the timer is running.
+    /// When attached to another instance, it only mirrors the state of that instance.
+    pub fn tick(&mut self) {
+        self.forget_stale_cycle_selection();
+        self.attach_commits();
+
+        if self.is_attached() {
+            self.mirror_owner();
+            return;
+        }
+
+        if self.is_long_pause_reminder_due() {
+            self.long_pause_reminders += 1;
+            self.notifiers.notify(&Event::LongPause {
+                paused_for: Duration::from_secs(self.get_pause_elapsed_time()),
+            });
+        }
+
+        let (is_due, _) = self.get_remaining_time();
+
+        if !is_due && !self.pre_end_warned && self.is_stage_ending() {
+            self.pre_end_warned = true;
+            self.notifiers.notify(&Event::StageEnding {
+                stage: *self.get_current_stage(),
+                remaining: self.get_remaining_duration(),
+            });
+        }
+
+        if is_due {
+            self.current_cycle.completed = true;
+            self.finish_current_cycle();
+        }
+
+        let running = self.current_cycle.started_at.is_some() && !self.is_paused();
+        if running != self.timer_running {
+            self.timer_running = running;
+            self.notifiers.notify(&Event::TimerRunning {
+                stage: *self.get_current_stage(),
+                running,
+            });
+        }
+    }
+
     pub fn finish_current_cycle(&mut self) {
+        if self.is_attached() {
+            self.send_to_owner(ControlCommand::Finish);
+            return;
+        }
+
+        let finished_at = Instant::now();
+
         if self.current_cycle.finished_at.is_none() {
-            self.current_cycle.finished_at = Some(Instant::now());
+            self.current_cycle.finished_at = Some(finished_at);
+        }
+
+        if let Some(mut interruption) = self.current_cycle.interruption.take() {
+            interruption.finished_at = Some(finished_at);
+            self.finish_interruption(interruption);
+        }
+
+        if let AppView::AnnotationPopup = self.current_view {
+            self.set_view(AppView::Normal);
+        }
+
+        let commits = match self.current_cycle.stage {
+            Stage::Work => self.look_up_commits(),
+            _ => None,
+        };
+
+        let mut record = None;
+        if let Some(finished) = CycleRecord::from_cycle(&self.current_cycle) {
+            // Otherwise, it's stored once its commits are in
+            if commits.is_none() {
+                self.store_record(&finished);
+            }
+            record = Some(self.records.len());
+            self.records.push(finished);
         }
 
         self.history.push(self.current_cycle.clone());
-        self.current_cycle = Cycle::new(self.current_cycle.stage_iteration + 1);
-
-        if !wsl::is_wsl() {
-            if let Some(device) = rodio::default_output_device() {
-                if let Ok(file) = File::open("sounds/beep.wav") {
-                    if let Ok(src) = rodio::Decoder::new(BufReader::new(file)) {
-                        rodio::play_raw(
-                            &device,
-                            src.take_duration(Duration::from_secs(1)).convert_samples(),
-                        );
-                    }
-                }
+        if let Some(commits) = commits {
+            self.commit_lookups.push(CommitLookup {
+                cycle: self.history.len() - 1,
+                record,
+                commits,
+            });
+        }
+
+        let finished_stage = self.current_cycle.stage;
+        let completed = self.current_cycle.completed;
+        let was_started = self.current_cycle.started_at.is_some();
+        let stage_iteration = self.current_cycle.stage_iteration + 1;
+        self.current_cycle = Cycle::new(stage_iteration, self.get_stage_at(stage_iteration));
+        self.current_cycle.task = self.task.clone();
+        self.pre_end_warned = false;
+
+        // Skipping a stage before it started isn't worth telling about, e.g. as a voided pomodoro
+        if !was_started {
+            return;
+        }
+
+        let next_stage = self.current_cycle.stage;
+        self.notifiers.notify(&Event::StageFinished {
+            stage: finished_stage,
+            completed,
+            next_stage,
+            next_duration: self.get_stage_duration(&next_stage),
+            daily_goal_reached: finished_stage == Stage::Work && self.has_just_reached_daily_goal(),
+        });
+    }
+
+    fn store_record(&self, record: &CycleRecord) {
+        if let Some(store) = self.history_store.as_ref() {
+            // Losing a record isn't worth interrupting the session over
+            store.append(record).unwrap_or_default();
+        }
+    }
+
+    /// Starts looking up the commits made while the current cycle ran, in every
+    /// configured repository, on a thread of its own so that git doesn't hold up the timer.
+    fn look_up_commits(&self) -> Option<Receiver<Vec<Commit>>> {
+        let (started_at, finished_at) = match (
+            self.current_cycle.started_at,
+            self.current_cycle.finished_at,
+        ) {
+            (Some(started_at), Some(finished_at)) if self.git.enabled => {
+                (wall_clock(started_at), wall_clock(finished_at))
+            }
+            _ => return None,
+        };
+
+        let repositories = self.git.repositories.clone();
+        let (send_commits, commits) = mpsc::channel();
+        thread::spawn(move || {
+            // A repository which went missing shouldn't cost the commits of the others
+            let mut commits: Vec<Commit> = repositories
+                .iter()
+                .filter_map(|repository| {
+                    git::commits_between(repository, started_at, finished_at).ok()
+                })
+                .flatten()
+                .collect();
+            commits.sort_by_key(|commit| commit.committed_at);
+
+            send_commits.send(commits).unwrap_or_default();
+        });
+
+        Some(commits)
+    }
+
+    /// Attaches the commits which have been looked up since the last tick to their cycles.
+    fn attach_commits(&mut self) {
+        let mut lookups = mem::take(&mut self.commit_lookups);
+        lookups.retain(|lookup| {
+            let commits = match lookup.commits.try_recv() {
+                Ok(commits) => commits,
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => Vec::new(),
+            };
+            self.finish_commit_lookup(lookup, commits);
+            false
+        });
+        self.commit_lookups = lookups;
+    }
+
+    /// Waits for the commits still being looked up, so that the records waiting
+    /// on them are stored before exiting.
+    pub fn finish_commit_lookups(&mut self) {
+        for lookup in mem::take(&mut self.commit_lookups) {
+            let commits = lookup.commits.recv().unwrap_or_default();
+            self.finish_commit_lookup(&lookup, commits);
+        }
+    }
+
+    fn finish_commit_lookup(&mut self, lookup: &CommitLookup, commits: Vec<Commit>) {
+        if let Some(cycle) = self.history.get_mut(lookup.cycle) {
+            cycle.commits = commits.clone();
+        }
+
+        if let Some(record) = lookup
+            .record
+            .and_then(|record| self.records.get_mut(record))
+        {
+            record.commits = commits;
+            let record = record.clone();
+            self.store_record(&record);
+        }
+    }
+
+    /// Whether the last record is the pomodoro which completed today's goal.
+    fn has_just_reached_daily_goal(&self) -> bool {
+        let today = Local::now().date_naive();
+
+        match self.records.last() {
+            Some(record) if record.is_pomodoro() && record.started_at.date_naive() == today => {
+                self.daily_goal > 0 && self.get_todays_pomodoro_count() == self.daily_goal as usize
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control;
+    use crate::notifier::RecordingNotifier;
+
+    #[test]
+    fn tells_the_notifiers_about_starting_and_pausing_the_timer() {
+        let recorder = RecordingNotifier::default();
+        let notifiers = Notifiers::new(vec![vec![Box::new(recorder.clone())]]);
+        let mut app = App::new(Config::default(), None, notifiers).unwrap();
+
+        app.toggle_timer();
+        app.tick();
+        app.toggle_timer();
+
+        let events = recorder.events();
+        assert_eq!(
+            events[0],
+            Event::StageStarted {
+                stage: Stage::Work,
+                duration: Duration::from_secs(25 * 60),
+            }
+        );
+        assert!(events.contains(&Event::TimerRunning {
+            stage: Stage::Work,
+            running: true,
+        }));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            Event::PauseStarted {
+                stage: Stage::Work,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn keeps_quiet_about_skipping_stages_which_never_started() {
+        let recorder = RecordingNotifier::default();
+        let notifiers = Notifiers::new(vec![vec![Box::new(recorder.clone())]]);
+        let mut app = App::new(Config::default(), None, notifiers).unwrap();
+
+        app.finish_current_cycle();
+        assert_eq!(app.get_current_stage(), &Stage::ShortBreak);
+        assert!(!recorder
+            .events()
+            .iter()
+            .any(|event| matches!(event, Event::StageFinished { .. })));
+
+        app.toggle_timer();
+        app.finish_current_cycle();
+        assert!(recorder.events().iter().any(|event| matches!(
+            event,
+            Event::StageFinished {
+                stage: Stage::ShortBreak,
+                completed: false,
+                ..
             }
+        )));
+    }
+
+    #[test]
+    fn only_asks_about_pauses_made_from_this_instance() {
+        let mut app = App::default();
+
+        // e.g. `pomodoro toggle`, with nobody in front of a daemon to close the popup
+        control::handle(&mut app, ControlCommand::Toggle);
+        control::handle(&mut app, ControlCommand::Toggle);
+        assert!(app.is_paused());
+        assert_eq!(app.get_view(), &AppView::Normal);
+
+        control::handle(
+            &mut app,
+            ControlCommand::Annotate {
+                text: String::from("phone call"),
+            },
+        );
+        app.toggle_timer_and_annotate();
+        app.toggle_timer_and_annotate();
+        assert!(app.is_paused());
+        assert_eq!(app.get_view(), &AppView::AnnotationPopup);
+        assert_eq!(app.get_annotation_input().value(), "");
+    }
+
+    #[test]
+    fn records_the_commits_of_work_cycles_once_they_are_found() {
+        let dir = env::temp_dir().join(format!("pomodoro-commits-{}", std::process::id()));
+        let repository = dir.join("engine");
+        std::fs::create_dir_all(&repository).unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(&repository)
+                .args(args)
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return;
         }
+        // Not a valid regex, should it be taken for one
+        git(&["config", "user.email", "Ada+Work@example.com"]);
+        git(&["config", "user.name", "Ada"]);
+
+        let config = Config {
+            git: GitConfig {
+                enabled: true,
+                repositories: vec![repository.clone()],
+            },
+            ..Config::default()
+        };
+        let store = HistoryStore::new(dir.join("history.jsonl"));
+        let mut app = App::new(config, Some(store), Notifiers::default()).unwrap();
+
+        // git only knows about whole seconds, so the cycle is stretched around the commits
+        // rather than waited out
+        app.toggle_timer();
+        app.current_cycle.started_at = Some(Instant::now() - Duration::from_secs(2));
+        assert!(git(&["commit", "-q", "--allow-empty", "-m", "Mine"]));
+        assert!(git(&[
+            "-c",
+            "user.email=bob@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Theirs",
+        ]));
+        app.current_cycle.finished_at = Some(Instant::now() + Duration::from_secs(2));
+        app.finish_current_cycle();
+        app.finish_commit_lookups();
+
+        let records = HistoryStore::new(dir.join("history.jsonl")).load().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap_or_default();
+        assert_eq!(records.len(), 1);
+        let summaries: Vec<_> = records[0]
+            .commits
+            .iter()
+            .map(|commit| commit.summary.as_str())
+            .collect();
+        assert_eq!(summaries, vec!["Mine"]);
+        assert_eq!(app.history[0].commits.len(), 1);
     }
 }
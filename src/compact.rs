@@ -0,0 +1,173 @@
+use std::{io::Write, time::Duration};
+
+use crossterm::{
+    cursor, queue,
+    terminal::{self, Clear, ClearType},
+    Result,
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::app::{App, AppView};
+use crate::history::{pomodoros_by_day, streaks};
+use crate::widgets::{format_duration, TextInput, CHARTS_WINDOW_DAYS};
+
+/// Terminals with fewer rows than this can't fit the regular layout,
+/// so the compact mode is used instead.
+pub const MIN_REGULAR_LAYOUT_ROWS: u16 = 20;
+
+/// Reserves the two lines used by the compact mode.
+/// Between draws, the cursor rests on the second one.
+pub fn enter<W: Write>(out: &mut W) -> Result<()> {
+    queue!(out, cursor::Hide)?;
+    write!(out, "\r\n")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Moves the cursor past the compact mode lines, leaving them on screen.
+pub fn leave<W: Write>(out: &mut W) -> Result<()> {
+    queue!(out, cursor::Show)?;
+    write!(out, "\r\n")?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Renders the app state on two lines, in place.
+///
+/// The first line holds the stage, the remaining time, the pause timer and the
+/// interruption count; the second one holds whatever the current view needs.
+pub fn draw<W: Write>(out: &mut W, app: &App) -> Result<()> {
+    let (width, _) = terminal::size()?;
+    let width = width as usize;
+
+    let (_, remaining_time) = app.get_remaining_time();
+
+    let mut status = format!("{} {}", app.get_current_stage(), remaining_time);
+
+    if app.is_paused() {
+        let has_been_paused_for = app.get_pause_elapsed_time();
+        status.push_str(&format!(
+            " | paused {:02}:{:02}",
+            has_been_paused_for / 60,
+            has_been_paused_for % 60
+        ));
+    }
+
+    status.push_str(&format!(
+        " | interruptions {}",
+        app.get_interruption_count()
+    ));
+
+    if let Some(task) = app.get_task() {
+        status.push_str(&format!(" | {}", task));
+    }
+
+    let mut prompt_cursor = None;
+
+    let details = match app.get_view() {
+        AppView::AnnotationPopup => {
+            let (line, cursor) = prompt("> ", app.get_annotation_input(), width);
+            prompt_cursor = Some(cursor);
+            line
+        }
+        AppView::TaskPrompt => {
+            let (line, cursor) = prompt("task> ", app.get_task_input(), width);
+            prompt_cursor = Some(cursor);
+            line
+        }
+        AppView::History => {
+            let cycles = app.get_todays_history();
+            let focused = cycles.iter().fold(Duration::new(0, 0), |total, cycle| {
+                total + cycle.focused_time()
+            });
+            format!(
+                "today: {} cycles, focused {}",
+                cycles.len(),
+                format_duration(focused)
+            )
+        }
+        AppView::InterruptionsList => app
+            .get_interruption_history()
+            .iter()
+            .map(|interruption| {
+                let duration = interruption.duration().as_secs();
+                match interruption.annotation.as_ref() {
+                    Some(annotation) => format!("{}s {}", duration, annotation),
+                    None => format!("{}s", duration),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", "),
+        AppView::Charts => {
+            let last_day = app.get_charts_last_day();
+            let first_day = last_day - chrono::Duration::days(CHARTS_WINDOW_DAYS as i64 - 1);
+            let pomodoros = app
+                .get_records()
+                .iter()
+                .filter(|record| {
+                    let day = record.started_at.date_naive();
+                    record.is_pomodoro() && day >= first_day && day <= last_day
+                })
+                .count();
+            format!(
+                "{} - {}: {} pomodoros",
+                first_day.format("%b %d"),
+                last_day.format("%b %d"),
+                pomodoros
+            )
+        }
+        AppView::Heatmap => {
+            let today = chrono::Local::now().date_naive();
+            let (current, longest) = streaks(&pomodoros_by_day(app.get_records()), today);
+            format!(
+                "current streak: {} days, longest streak: {} days",
+                current, longest
+            )
+        }
+        AppView::Normal => String::new(),
+    };
+
+    queue!(
+        out,
+        cursor::MoveToPreviousLine(1),
+        Clear(ClearType::CurrentLine)
+    )?;
+    write!(out, "{}", truncate(&status, width))?;
+    queue!(
+        out,
+        cursor::MoveToNextLine(1),
+        Clear(ClearType::CurrentLine)
+    )?;
+
+    match prompt_cursor {
+        Some(column) => {
+            write!(out, "{}", details)?;
+            queue!(out, cursor::MoveToColumn(column + 1), cursor::Show)?;
+        }
+        None => {
+            write!(out, "{}", truncate(&details, width))?;
+            queue!(out, cursor::Hide)?;
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Lays out a text prompt, returning the line and the cursor column on it.
+fn prompt(label: &str, input: &TextInput, width: usize) -> (String, u16) {
+    let (visible, cursor) = input.visible(width.saturating_sub(label.len()) as u16);
+    (format!("{}{}", label, visible), label.len() as u16 + cursor)
+}
+
+/// Cuts `line` so it doesn't wrap around a terminal `width` columns wide.
+fn truncate(line: &str, width: usize) -> String {
+    let mut columns = 0;
+    line.chars()
+        .take_while(|c| {
+            columns += c.width().unwrap_or(0);
+            columns < width
+        })
+        .collect()
+}
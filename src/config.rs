@@ -0,0 +1,582 @@
+use std::{
+    convert::TryFrom,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::notifier::{Chord, NotifierKind, WEBHOOK_EVENTS};
+use crate::pomodoro_technique::Stage;
+
+/// Settings read from the config file; anything left out keeps its default.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub timer: TimerConfig,
+    /// Pomodoros to complete in a day, celebrated with its own sound
+    pub daily_goal: u32,
+    pub long_pause: LongPauseConfig,
+    pub pre_end_warning: PreEndWarningConfig,
+    /// Groups of notifiers which every event is sent to; within a group,
+    /// each notifier is a fallback for the ones before it
+    pub notifiers: Vec<Vec<NotifierKind>>,
+    pub hooks: HooksConfig,
+    pub webhooks: Vec<WebhookConfig>,
+    pub git: GitConfig,
+    pub sounds: SoundsConfig,
+    pub ticking: TickingConfig,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            timer: TimerConfig::default(),
+            daily_goal: 8,
+            long_pause: LongPauseConfig::default(),
+            pre_end_warning: PreEndWarningConfig::default(),
+            notifiers: vec![
+                vec![NotifierKind::Sound, NotifierKind::Bell],
+                vec![NotifierKind::Desktop, NotifierKind::Terminal],
+            ],
+            hooks: HooksConfig::default(),
+            webhooks: Vec::new(),
+            git: GitConfig::default(),
+            sounds: SoundsConfig::default(),
+            ticking: TickingConfig::default(),
+        }
+    }
+}
+
+/// Durations of the stages and the order they come in; anything left out
+/// keeps the built-in plan, e.g. in the config file:
+///
+/// ```toml
+/// [timer]
+/// work = "50m"
+/// short_break = "10m"
+/// sequence = ["work", "short_break", "work", "long_break"]
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimerConfig {
+    #[serde(with = "humantime_serde")]
+    pub work: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub short_break: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub long_break: Option<Duration>,
+    /// Stages of one round, repeated over and over
+    pub sequence: Option<Vec<Stage>>,
+}
+
+impl TimerConfig {
+    /// Rejects plans the timer can't run: stages lasting no time at all,
+    /// which would be skipped over forever, or an empty sequence.
+    pub fn validate(&self) -> Result<(), String> {
+        let durations = [&self.work, &self.short_break, &self.long_break];
+        if durations
+            .iter()
+            .any(|duration| **duration == Some(Duration::new(0, 0)))
+        {
+            return Err(String::from("stage durations must be longer than zero"));
+        }
+
+        match self.sequence.as_ref() {
+            Some(sequence) if sequence.is_empty() => {
+                Err(String::from("the stage sequence can't be empty"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// When a pause is running for too long, in minutes since it started.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LongPauseConfig {
+    /// The pause timer turns yellow
+    pub warn_after: u64,
+    /// The pause timer turns red and the first reminder goes out
+    pub remind_after: u64,
+    /// Reminders repeat at this interval until the pause ends; 0 reminds only once
+    pub remind_every: u64,
+}
+
+impl Default for LongPauseConfig {
+    fn default() -> LongPauseConfig {
+        LongPauseConfig {
+            warn_after: 5,
+            remind_after: 10,
+            remind_every: 5,
+        }
+    }
+}
+
+/// How long before the end of each stage to warn that it is ending,
+/// in seconds; 0 turns the warning off.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreEndWarningConfig {
+    pub work: u64,
+    pub short_break: u64,
+    pub long_break: u64,
+}
+
+impl Default for PreEndWarningConfig {
+    fn default() -> PreEndWarningConfig {
+        PreEndWarningConfig {
+            work: 60,
+            short_break: 0,
+            long_break: 60,
+        }
+    }
+}
+
+/// Shell commands run on each event, with its details in `POMODORO_*` environment
+/// variables, e.g. in the config file:
+///
+/// ```toml
+/// [hooks]
+/// stage_started = 'if [ "$POMODORO_STAGE" = work ]; then makoctl mode -a do-not-disturb; fi'
+/// stage_finished = "makoctl mode -r do-not-disturb"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    pub stage_started: Option<String>,
+    pub stage_finished: Option<String>,
+    pub paused: Option<String>,
+    pub resumed: Option<String>,
+    /// Run once the annotated interruption is over
+    pub annotation_saved: Option<String>,
+    pub daily_goal_reached: Option<String>,
+    /// Seconds a hook may run before it gets killed
+    pub timeout: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> HooksConfig {
+        HooksConfig {
+            stage_started: None,
+            stage_finished: None,
+            paused: None,
+            resumed: None,
+            annotation_saved: None,
+            daily_goal_reached: None,
+            timeout: 30,
+        }
+    }
+}
+
+impl HooksConfig {
+    pub fn is_any_set(&self) -> bool {
+        [
+            &self.stage_started,
+            &self.stage_finished,
+            &self.paused,
+            &self.resumed,
+            &self.annotation_saved,
+            &self.daily_goal_reached,
+        ]
+        .iter()
+        .any(|hook| hook.is_some())
+    }
+}
+
+/// A URL receiving a JSON POST on each of the events it is interested in,
+/// e.g. in the config file:
+///
+/// ```toml
+/// [[webhooks]]
+/// url = "https://dashboard.example.com/pomodoro"
+/// events = ["pomodoro_completed", "pomodoro_voided"]
+/// secret = "shared secret"
+/// template = '{"user": "ada", "stage": "{stage}", "at": "{time}"}'
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Names of the events to send, as in the event stream, or `pomodoro_completed`,
+    /// `pomodoro_voided` and `daily_goal_reached` for the matching `stage_finished` events
+    #[serde(default = "WebhookConfig::default_events")]
+    pub events: Vec<String>,
+    /// How long to wait for each attempt at delivering an event
+    #[serde(default = "WebhookConfig::default_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+    /// Attempts after the first failed one, each waiting twice as long as the one before
+    #[serde(default = "WebhookConfig::default_retries")]
+    pub retries: u32,
+    /// Signs every payload with HMAC-SHA256, in the `X-Pomodoro-Signature` header
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Body to send instead of the event itself, where `{field}` is replaced
+    /// by that field of the event, escaped for a JSON string, and `{event_json}` by the whole event
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+impl WebhookConfig {
+    fn default_events() -> Vec<String> {
+        vec![String::from("stage_finished")]
+    }
+
+    fn default_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn default_retries() -> u32 {
+        3
+    }
+
+    /// Rejects event names which no event would ever match.
+    pub fn validate(&self) -> Result<(), String> {
+        match self
+            .events
+            .iter()
+            .find(|name| !WEBHOOK_EVENTS.contains(&name.as_str()))
+        {
+            Some(name) => Err(format!(
+                "unknown event `{}` for the webhook {}, expected one of {}",
+                name,
+                self.url,
+                WEBHOOK_EVENTS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Records, at the end of each work stage, the commits made during it, e.g.
+/// in the config file:
+///
+/// ```toml
+/// [git]
+/// enabled = true
+/// repositories = ["/home/ada/src/engine", "/home/ada/src/docs"]
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    pub enabled: bool,
+    /// Repositories to look for commits in; the one of the working directory when empty.
+    /// Relative paths are relative to the config file
+    pub repositories: Vec<PathBuf>,
+}
+
+/// The sound of each event, e.g. in the config file:
+///
+/// ```toml
+/// [sounds.work_end]
+/// file = "gong.wav"
+/// volume = 0.5
+/// duration = 3.0
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundsConfig {
+    pub work_end: SoundConfig,
+    pub break_end: SoundConfig,
+    pub pause_started: SoundConfig,
+    pub long_pause_reminder: SoundConfig,
+    pub daily_goal_reached: SoundConfig,
+    pub stage_ending: SoundConfig,
+}
+
+impl Default for SoundsConfig {
+    fn default() -> SoundsConfig {
+        SoundsConfig {
+            work_end: SoundConfig::melody(&[("G5", 0.15), ("E5", 0.15), ("C5", 0.3)]),
+            break_end: SoundConfig::melody(&[("C5", 0.15), ("E5", 0.15), ("G5", 0.3)]),
+            pause_started: SoundConfig {
+                enabled: false,
+                ..SoundConfig::default()
+            },
+            long_pause_reminder: SoundConfig::default(),
+            daily_goal_reached: SoundConfig::melody(&[
+                ("C5", 0.12),
+                ("E5", 0.12),
+                ("G5", 0.12),
+                ("C5 E5 G5 C6", 0.5),
+            ]),
+            stage_ending: SoundConfig {
+                volume: 0.3,
+                ..SoundConfig::melody(&[("E6", 0.3)])
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundConfig {
+    pub enabled: bool,
+    /// Sound file to play instead of the default beep;
+    /// relative paths are relative to the config file, and `~` is the home directory
+    pub file: Option<PathBuf>,
+    /// Notes to synthesize instead of the default beep, when there is no file, e.g.
+    /// `melody = [{ note = "C5", duration = 0.2 }, { note = "C5 E5 G5", duration = 0.4 }]`
+    pub melody: Vec<MelodyNote>,
+    /// From 0.0 (muted) to 1.0 (the file's own volume)
+    pub volume: f32,
+    /// Seconds of the sound to play; when left out, the whole file or melody,
+    /// but only the first second of the default beep
+    pub duration: Option<f32>,
+}
+
+impl Default for SoundConfig {
+    fn default() -> SoundConfig {
+        SoundConfig {
+            enabled: true,
+            file: None,
+            melody: Vec::new(),
+            volume: 1.0,
+            duration: None,
+        }
+    }
+}
+
+impl SoundConfig {
+    /// Rejects volumes and durations which can't be played.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_volume(self.volume)?;
+
+        let durations = self
+            .duration
+            .iter()
+            .chain(self.melody.iter().map(|note| &note.duration));
+        for &duration in durations {
+            if !(duration.is_finite() && duration > 0.0) {
+                return Err(format!(
+                    "invalid duration {}, expected a number of seconds above zero",
+                    duration
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn melody(notes: &[(&str, f32)]) -> SoundConfig {
+        let melody = notes
+            .iter()
+            .map(|(note, duration)| MelodyNote {
+                note: Chord::try_from(note.to_string()).expect("built-in melodies are valid"),
+                duration: *duration,
+            })
+            .collect();
+
+        SoundConfig {
+            melody,
+            ..SoundConfig::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MelodyNote {
+    /// Note names played together, like `"C5"` or `"F#4 A4"`; `"rest"` for silence
+    pub note: Chord,
+    /// In seconds
+    pub duration: f32,
+}
+
+/// The kitchen-timer tick played while a work stage is running.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TickingConfig {
+    pub enabled: bool,
+    pub volume: f32,
+}
+
+impl TickingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_volume(self.volume)
+    }
+}
+
+fn validate_volume(volume: f32) -> Result<(), String> {
+    match (0.0..=1.0).contains(&volume) {
+        true => Ok(()),
+        false => Err(format!(
+            "invalid volume {}, expected a number from 0.0 (muted) to 1.0",
+            volume
+        )),
+    }
+}
+
+impl Default for TickingConfig {
+    fn default() -> TickingConfig {
+        TickingConfig {
+            enabled: false,
+            volume: 0.3,
+        }
+    }
+}
+
+impl Config {
+    /// `config.toml` under the platform's config directory,
+    /// e.g. `~/.config/pomodoro/config.toml` on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("pomodoro").join("config.toml"))
+    }
+
+    /// Reads the config file at `path`; a missing file gives the defaults.
+    pub fn load(path: &Path) -> io::Result<Config> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(err),
+        };
+
+        let invalid_data = |err: String| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        };
+
+        let mut config: Config =
+            toml::from_str(&contents).map_err(|err| invalid_data(err.to_string()))?;
+        config.timer.validate().map_err(invalid_data)?;
+        for webhook in config.webhooks.iter() {
+            webhook.validate().map_err(invalid_data)?;
+        }
+        config.sounds.validate().map_err(invalid_data)?;
+        config
+            .ticking
+            .validate()
+            .map_err(|err| invalid_data(format!("ticking: {}", err)))?;
+
+        if let Some(dir) = path.parent() {
+            config.sounds.resolve_paths(dir);
+            for repository in config.git.repositories.iter_mut() {
+                *repository = dir.join(expand_home(repository));
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// `path` with a leading `~` replaced by the home directory, as a shell would.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(path), Some(home)) => home.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl SoundsConfig {
+    fn validate(&self) -> Result<(), String> {
+        let sounds = [
+            ("work_end", &self.work_end),
+            ("break_end", &self.break_end),
+            ("pause_started", &self.pause_started),
+            ("long_pause_reminder", &self.long_pause_reminder),
+            ("daily_goal_reached", &self.daily_goal_reached),
+            ("stage_ending", &self.stage_ending),
+        ];
+
+        for (name, sound) in sounds {
+            sound
+                .validate()
+                .map_err(|err| format!("sounds.{}: {}", name, err))?;
+        }
+
+        Ok(())
+    }
+
+    fn resolve_paths(&mut self, dir: &Path) {
+        let sounds = [
+            &mut self.work_end,
+            &mut self.break_end,
+            &mut self.pause_started,
+            &mut self.long_pause_reminder,
+            &mut self.daily_goal_reached,
+            &mut self.stage_ending,
+        ];
+
+        for sound in sounds {
+            if let Some(file) = sound.file.as_mut() {
+                *file = dir.join(expand_home(file));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(contents: &str) -> io::Result<Config> {
+        let dir =
+            std::env::temp_dir().join(format!("pomodoro-config-{:?}", std::thread::current().id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("config.toml");
+        fs::write(&path, contents)?;
+
+        let config = Config::load(&path);
+        fs::remove_dir_all(&dir)?;
+        config
+    }
+
+    #[test]
+    fn sounds_play_whole_unless_given_a_duration() {
+        let config = load("[sounds.work_end]\nfile = \"gong.wav\"\n").unwrap();
+
+        assert_eq!(config.sounds.work_end.duration, None);
+        assert_eq!(config.sounds.break_end.duration, None);
+    }
+
+    #[test]
+    fn built_in_sounds_are_valid() {
+        assert_eq!(SoundsConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_sounds_which_can_not_be_played() {
+        let invalid = [
+            "[sounds.work_end]\nduration = -1.0\n",
+            "[sounds.work_end]\nduration = 0.0\n",
+            "[sounds.break_end]\nduration = nan\n",
+            "[sounds.stage_ending]\nvolume = -0.5\n",
+            "[sounds.stage_ending]\nvolume = inf\n",
+            "[sounds.stage_ending]\nvolume = 1.5\n",
+            "[ticking]\nvolume = nan\n",
+            "[sounds.work_end]\nmelody = [{ note = \"C5\", duration = -0.2 }]\n",
+            "[sounds.work_end]\nmelody = [{ note = \"C5\", duration = nan }]\n",
+        ];
+
+        for contents in invalid.iter() {
+            let err = load(contents).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{}", contents);
+        }
+    }
+
+    #[test]
+    fn finds_files_in_the_home_directory() {
+        let home = match dirs::home_dir() {
+            Some(home) => home,
+            None => return,
+        };
+        let config = load(concat!(
+            "[git]\nrepositories = [\"~/src/engine\", \"~\", \"docs\"]\n",
+            "[sounds.work_end]\nfile = \"~/sounds/gong.wav\"\n",
+        ))
+        .unwrap();
+
+        assert_eq!(config.git.repositories[0], home.join("src/engine"));
+        assert_eq!(
+            config.sounds.work_end.file,
+            Some(home.join("sounds/gong.wav"))
+        );
+        assert_eq!(config.git.repositories[1], home);
+        assert!(config.git.repositories[2].ends_with("docs"));
+        assert!(config.git.repositories[2].is_absolute());
+    }
+}
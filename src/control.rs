@@ -0,0 +1,405 @@
+use std::{
+    env, fmt,
+    fs::{self, DirBuilder, Permissions},
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::{App, AppConfiguration};
+use crate::pomodoro_technique::{Cycle, Stage};
+
+/// Bumped whenever requests or responses change in a way older peers can't follow.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// A command sent to a running instance, as one line of JSON, e.g.
+/// `{"version":3,"command":"annotate","text":"phone call"}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub version: u32,
+    #[serde(flatten)]
+    pub command: ControlCommand,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    Toggle,
+    Pause,
+    Resume,
+    Finish,
+    Status,
+    Annotate {
+        text: String,
+    },
+    SetTask {
+        task: Option<String>,
+    },
+    /// Asks for the whole timer state, for clients which mirror it
+    Snapshot,
+}
+
+/// The answer to a request, as one line of JSON.
+/// Successful requests get the state of the timer after running the command.
+#[derive(Serialize, Deserialize)]
+pub struct Response {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Everything a client needs to mirror the timer of the instance owning it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub current_cycle: Cycle,
+    pub history: Vec<Cycle>,
+    pub task: Option<String>,
+    /// The stage durations and sequence of the owner, which may differ from the client's
+    pub timer: AppConfiguration,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Status {
+    pub stage: Stage,
+    pub remaining_secs: u64,
+    pub started: bool,
+    pub paused: bool,
+    pub paused_secs: u64,
+    pub interruptions: usize,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub pomodoros_today: usize,
+    /// How many cycles came before the current one since the timer started
+    #[serde(default)]
+    pub cycle: usize,
+}
+
+impl Status {
+    pub fn from_app(app: &App) -> Status {
+        Status {
+            stage: *app.get_current_stage(),
+            remaining_secs: app.get_remaining_duration().as_secs(),
+            started: app.is_started(),
+            paused: app.is_paused(),
+            paused_secs: app.get_pause_elapsed_time(),
+            interruptions: app.get_interruption_count(),
+            task: app.get_task().map(String::from),
+            pomodoros_today: app.get_todays_pomodoro_count(),
+            cycle: app.get_cycle_number(),
+        }
+    }
+
+    /// Whether the timer changed state since `last_status`, by more than the passing of time.
+    pub fn has_changed_state(&self, last_status: &Status) -> bool {
+        last_status.stage != self.stage
+            || last_status.cycle != self.cycle
+            || last_status.started != self.started
+            || last_status.paused != self.paused
+            || last_status.interruptions != self.interruptions
+            || last_status.task != self.task
+            || last_status.pomodoros_today != self.pomodoros_today
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:02}:{:02}",
+            self.stage,
+            self.remaining_secs / 60,
+            self.remaining_secs % 60
+        )?;
+
+        match (self.started, self.paused) {
+            (false, _) => write!(f, " | not started")?,
+            (true, true) => write!(
+                f,
+                " | paused {:02}:{:02}",
+                self.paused_secs / 60,
+                self.paused_secs % 60
+            )?,
+            (true, false) => {}
+        }
+
+        write!(f, " | interruptions {}", self.interruptions)?;
+
+        if let Some(task) = self.task.as_ref() {
+            write!(f, " | {}", task)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Response {
+    fn ok(status: Status) -> Response {
+        Response {
+            version: PROTOCOL_VERSION,
+            status: Some(status),
+            snapshot: None,
+            error: None,
+        }
+    }
+
+    fn error(error: String) -> Response {
+        Response {
+            version: PROTOCOL_VERSION,
+            status: None,
+            snapshot: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Runs `command` through the same `App` methods as the keyboard.
+pub fn handle(app: &mut App, command: ControlCommand) -> Response {
+    let wants_snapshot = matches!(command, ControlCommand::Snapshot);
+
+    let result = match command {
+        ControlCommand::Toggle => {
+            app.toggle_timer();
+            Ok(())
+        }
+        ControlCommand::Pause => match (app.is_started(), app.is_paused()) {
+            (true, false) => {
+                app.toggle_timer();
+                Ok(())
+            }
+            (false, _) => Err("the timer hasn't started"),
+            (true, true) => Err("the timer is already paused"),
+        },
+        ControlCommand::Resume => match (app.is_started(), app.is_paused()) {
+            (true, true) | (false, _) => {
+                app.toggle_timer();
+                Ok(())
+            }
+            (true, false) => Err("the timer is already running"),
+        },
+        ControlCommand::Finish => {
+            app.finish_current_cycle();
+            Ok(())
+        }
+        ControlCommand::Status | ControlCommand::Snapshot => Ok(()),
+        ControlCommand::SetTask { task } => {
+            app.set_task(task);
+            Ok(())
+        }
+        ControlCommand::Annotate { text } => match app.annotate_interruption(&text) {
+            true => Ok(()),
+            false => Err("the timer isn't paused, so there is no interruption to annotate"),
+        },
+    };
+
+    match result {
+        Ok(()) => Response {
+            snapshot: match wants_snapshot {
+                true => Some(app.snapshot()),
+                false => None,
+            },
+            ..Response::ok(Status::from_app(app))
+        },
+        Err(error) => Response::error(String::from(error)),
+    }
+}
+
+/// `pomodoro.sock` under the user's runtime directory,
+/// or a private per-user directory in the temporary directory where there is none.
+pub fn socket_path() -> io::Result<PathBuf> {
+    runtime_dir().map(|dir| dir.join("pomodoro.sock"))
+}
+
+/// `pomodoro.json` next to the control socket.
+pub fn state_path() -> io::Result<PathBuf> {
+    runtime_dir().map(|dir| dir.join("pomodoro.json"))
+}
+
+/// Anyone able to reach the socket could drive the timer and read the history,
+/// so the directory made up in the temporary directory is created private,
+/// and checked to still be, as another user may have made it first.
+fn runtime_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir);
+    }
+
+    let user = env::var("USER").unwrap_or_default();
+    let dir = env::temp_dir().join(format!("pomodoro-{}", user));
+
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Err(err) if err.kind() != ErrorKind::AlreadyExists => return Err(err),
+        _ => {}
+    }
+
+    let metadata = fs::symlink_metadata(&dir)?;
+    // SAFETY: geteuid can't fail, nor touch any memory
+    let uid = unsafe { libc::geteuid() };
+    match metadata.is_dir() && metadata.uid() == uid && metadata.mode() & 0o077 == 0 {
+        true => Ok(dir),
+        false => Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "{} should be a directory only the user can access",
+                dir.display()
+            ),
+        )),
+    }
+}
+
+/// Listens for requests on the control socket while alive,
+/// and removes the socket once dropped.
+pub struct ControlServer {
+    path: PathBuf,
+}
+
+impl ControlServer {
+    /// Starts accepting requests, each of which is passed to `forward`
+    /// along with where to send its response; `forward` fails once the app is exiting.
+    pub fn start<F>(forward: F) -> io::Result<ControlServer>
+    where
+        F: Fn(ControlCommand, Sender<Response>) -> bool + Clone + Send + 'static,
+    {
+        let path = socket_path()?;
+
+        if UnixStream::connect(&path).is_ok() {
+            return Err(io::Error::new(
+                ErrorKind::AddrInUse,
+                format!(
+                    "another instance is already listening on {}",
+                    path.display()
+                ),
+            ));
+        }
+        // Nobody is listening, so the socket is left over from an instance which crashed
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let forward = forward.clone();
+                thread::spawn(move || serve(stream, forward));
+            }
+        });
+
+        Ok(ControlServer { path })
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).unwrap_or_default();
+    }
+}
+
+fn serve<F>(stream: UnixStream, forward: F)
+where
+    F: Fn(ControlCommand, Sender<Response>) -> bool,
+{
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) if request.version != PROTOCOL_VERSION => Response::error(format!(
+                "unsupported protocol version {}, expected {}",
+                request.version, PROTOCOL_VERSION
+            )),
+            Ok(request) => dispatch(&forward, request.command),
+            Err(err) => Response::error(format!("invalid request: {}", err)),
+        };
+
+        let sent = serde_json::to_string(&response)
+            .map_err(io::Error::from)
+            .and_then(|response| writeln!(writer, "{}", response));
+        if sent.is_err() {
+            return;
+        }
+    }
+}
+
+/// Passes `command` on to the app through `forward`, and waits for its response.
+pub fn dispatch<F>(forward: &F, command: ControlCommand) -> Response
+where
+    F: Fn(ControlCommand, Sender<Response>) -> bool,
+{
+    let (respond, response) = mpsc::channel();
+
+    match forward(command, respond) {
+        true => response
+            .recv()
+            .unwrap_or_else(|_| Response::error(String::from("the app is exiting"))),
+        false => Response::error(String::from("the app is exiting")),
+    }
+}
+
+/// A connection to the running instance.
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    pub fn connect() -> io::Result<Client> {
+        let path = socket_path()?;
+
+        let stream = UnixStream::connect(&path).map_err(|err| match err.kind() {
+            ErrorKind::NotFound | ErrorKind::ConnectionRefused => io::Error::new(
+                err.kind(),
+                format!("no running instance is listening on {}", path.display()),
+            ),
+            _ => err,
+        })?;
+
+        Ok(Client {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// Sends `command` to the running instance and waits for its response.
+    pub fn send(&mut self, command: ControlCommand) -> io::Result<Response> {
+        let request = Request {
+            version: PROTOCOL_VERSION,
+            command,
+        };
+        writeln!(self.writer, "{}", serde_json::to_string(&request)?)?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "the running instance has exited",
+            ));
+        }
+
+        serde_json::from_str(&line).map_err(|err| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid response from the running instance: {}", err),
+            )
+        })
+    }
+}
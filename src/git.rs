@@ -0,0 +1,100 @@
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Separates the fields of each commit in the output of `git log`.
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// A commit made during a work stage.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Commit {
+    /// Name of the directory of the repository
+    pub repository: String,
+    pub hash: String,
+    pub summary: String,
+    pub committed_at: DateTime<Local>,
+}
+
+/// The commits of the user in the repository at `path` which were committed
+/// between `since` and `until`, on any branch, oldest first.
+pub fn commits_between(
+    path: &Path,
+    since: DateTime<Local>,
+    until: DateTime<Local>,
+) -> io::Result<Vec<Commit>> {
+    let repository = path
+        .canonicalize()?
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut log = Command::new("git");
+    log.arg("-C")
+        .arg(path)
+        .args(["log", "--all", "--no-merges", "--reverse"])
+        .arg(format!("--since={}", since.to_rfc3339()))
+        .arg(format!("--until={}", until.to_rfc3339()))
+        .arg("--format=%H%x1f%ct%x1f%ae%x1f%s");
+    let email = user_email(path);
+
+    let output = log.stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{} isn't a git repository",
+            path.display()
+        )));
+    }
+
+    let mut commits = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.splitn(4, FIELD_SEPARATOR);
+        let invalid = || io::Error::new(ErrorKind::InvalidData, "unexpected git log output");
+
+        let hash = fields.next().ok_or_else(invalid)?;
+        let committed_at = fields
+            .next()
+            .and_then(|timestamp| timestamp.parse().ok())
+            .and_then(|timestamp| Local.timestamp_opt(timestamp, 0).single())
+            .ok_or_else(invalid)?;
+        let author_email = fields.next().ok_or_else(invalid)?;
+        let summary = fields.next().unwrap_or_default();
+
+        // Commits fetched from the rest of the team aren't the user's own work.
+        // Compared here rather than with `--author`, which takes a regex
+        if let Some(email) = email.as_ref() {
+            if !author_email.eq_ignore_ascii_case(email) {
+                continue;
+            }
+        }
+
+        commits.push(Commit {
+            repository: repository.clone(),
+            hash: String::from(hash),
+            summary: String::from(summary),
+            committed_at,
+        });
+    }
+
+    Ok(commits)
+}
+
+fn user_email(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["config", "user.email"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match output.status.success() && !email.is_empty() {
+        true => Some(email),
+        false => None,
+    }
+}
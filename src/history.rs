@@ -0,0 +1,346 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::git::Commit;
+use crate::pomodoro_technique::{wall_clock, Cycle, Interruption, Stage};
+
+/// A finished cycle, as persisted across sessions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CycleRecord {
+    pub stage: Stage,
+    #[serde(default)]
+    pub task: Option<String>,
+    pub started_at: DateTime<Local>,
+    pub finished_at: DateTime<Local>,
+    /// Whether the cycle ran its full duration rather than being skipped
+    pub completed: bool,
+    pub focused_secs: u64,
+    pub paused_secs: u64,
+    #[serde(default)]
+    pub interruptions: Vec<InterruptionRecord>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<Commit>,
+}
+
+/// An interruption of a finished cycle, as persisted across sessions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InterruptionRecord {
+    pub started_at: DateTime<Local>,
+    pub finished_at: DateTime<Local>,
+    #[serde(default)]
+    pub annotation: Option<String>,
+}
+
+impl CycleRecord {
+    /// Builds the record of a finished cycle; cycles which were never started
+    /// leave nothing worth recording.
+    pub fn from_cycle(cycle: &Cycle) -> Option<CycleRecord> {
+        let (started_at, finished_at) = match (cycle.started_at, cycle.finished_at) {
+            (Some(started_at), Some(finished_at)) => (started_at, finished_at),
+            _ => return None,
+        };
+
+        Some(CycleRecord {
+            stage: cycle.stage,
+            task: cycle.task.clone(),
+            started_at: wall_clock(started_at),
+            finished_at: wall_clock(finished_at),
+            completed: cycle.completed,
+            focused_secs: cycle.focused_time().as_secs(),
+            paused_secs: cycle.paused_time().as_secs(),
+            interruptions: cycle
+                .interruption_history
+                .iter()
+                .map(InterruptionRecord::from_interruption)
+                .collect(),
+            commits: cycle.commits.clone(),
+        })
+    }
+
+    /// Whether this is a work stage that ran its full duration.
+    pub fn is_pomodoro(&self) -> bool {
+        self.stage == Stage::Work && self.completed
+    }
+}
+
+impl InterruptionRecord {
+    fn from_interruption(interruption: &Interruption) -> InterruptionRecord {
+        InterruptionRecord {
+            started_at: wall_clock(interruption.started_at),
+            finished_at: wall_clock(interruption.started_at + interruption.duration()),
+            annotation: interruption.annotation.clone(),
+        }
+    }
+}
+
+/// Finished cycles, stored one JSON object per line.
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf) -> HistoryStore {
+        HistoryStore { path }
+    }
+
+    /// `history.jsonl` under the platform's data directory,
+    /// e.g. `~/.local/share/pomodoro/history.jsonl` on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("pomodoro").join("history.jsonl"))
+    }
+
+    /// Reads every record in the store, oldest first.
+    /// Lines which can't be parsed are skipped rather than failing the whole history.
+    pub fn load(&self) -> io::Result<Vec<CycleRecord>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            if let Ok(record) = serde_json::from_str(&line?) {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    pub fn append(&self, record: &CycleRecord) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        let line = serde_json::to_string(record)?;
+        writeln!(file, "{}", line)
+    }
+}
+
+/// Number of pomodoros completed on each of the `days` days ending on `last_day`.
+pub fn pomodoros_per_day(
+    records: &[CycleRecord],
+    last_day: NaiveDate,
+    days: u32,
+) -> Vec<(NaiveDate, u64)> {
+    (0..days as i64)
+        .rev()
+        .map(|days_ago| {
+            let day = last_day - Duration::days(days_ago);
+            let count = records
+                .iter()
+                .filter(|record| record.is_pomodoro() && record.started_at.date_naive() == day)
+                .count();
+            (day, count as u64)
+        })
+        .collect()
+}
+
+/// Number of pomodoros completed on each day which had any.
+pub fn pomodoros_by_day(records: &[CycleRecord]) -> HashMap<NaiveDate, u64> {
+    let mut counts = HashMap::new();
+
+    for record in records.iter().filter(|record| record.is_pomodoro()) {
+        *counts.entry(record.started_at.date_naive()).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Current and longest runs of consecutive days with pomodoros, up to `last_day`.
+/// A `last_day` without pomodoros doesn't break the current streak, as the day may not be over.
+pub fn streaks(counts: &HashMap<NaiveDate, u64>, last_day: NaiveDate) -> (u32, u32) {
+    let had_pomodoros = |day: &NaiveDate| counts.get(day).is_some_and(|count| *count > 0);
+
+    let mut day = match had_pomodoros(&last_day) {
+        true => last_day,
+        false => last_day - Duration::days(1),
+    };
+    let mut current = 0;
+    while had_pomodoros(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    let mut days: Vec<&NaiveDate> = counts
+        .keys()
+        .filter(|day| **day <= last_day && had_pomodoros(day))
+        .collect();
+    days.sort();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        run = match previous {
+            Some(previous) if day - previous == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    (current, longest)
+}
+
+/// Minutes of focus on work stages within `first_day..=last_day`, by hour of the day.
+/// Each stage's focus is spread over the hours it spanned on the wall clock.
+pub fn focused_minutes_by_hour(
+    records: &[CycleRecord],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> [u64; 24] {
+    let mut seconds_by_hour = [0f64; 24];
+
+    for record in within(records, first_day, last_day) {
+        if record.stage != Stage::Work {
+            continue;
+        }
+
+        let span = (record.finished_at - record.started_at).num_seconds();
+        if span <= 0 {
+            continue;
+        }
+
+        let focus_ratio = record.focused_secs as f64 / span as f64;
+        let mut cursor = record.started_at;
+
+        while cursor < record.finished_at {
+            let next_hour = (cursor + Duration::hours(1))
+                .with_minute(0)
+                .and_then(|time| time.with_second(0))
+                .and_then(|time| time.with_nanosecond(0))
+                .unwrap_or(record.finished_at);
+            let until = next_hour.min(record.finished_at);
+
+            seconds_by_hour[cursor.hour() as usize] +=
+                (until - cursor).num_seconds() as f64 * focus_ratio;
+            cursor = until;
+        }
+    }
+
+    let mut minutes_by_hour = [0; 24];
+    for (minutes, seconds) in minutes_by_hour.iter_mut().zip(seconds_by_hour.iter()) {
+        *minutes = (seconds / 60.0).round() as u64;
+    }
+
+    minutes_by_hour
+}
+
+/// Interruption count of each pomodoro within `first_day..=last_day`, oldest first.
+pub fn interruptions_per_pomodoro(
+    records: &[CycleRecord],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> Vec<u64> {
+    within(records, first_day, last_day)
+        .filter(|record| record.is_pomodoro())
+        .map(|record| record.interruptions.len() as u64)
+        .collect()
+}
+
+fn within(
+    records: &[CycleRecord],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> impl Iterator<Item = &CycleRecord> {
+    records.iter().filter(move |record| {
+        let day = record.started_at.date_naive();
+        day >= first_day && day <= last_day
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    fn record(
+        stage: Stage,
+        started_at: DateTime<Local>,
+        minutes: i64,
+        focused_secs: u64,
+    ) -> CycleRecord {
+        CycleRecord {
+            stage,
+            task: None,
+            started_at,
+            finished_at: started_at + Duration::minutes(minutes),
+            completed: true,
+            focused_secs,
+            paused_secs: 0,
+            interruptions: Vec::new(),
+            commits: Vec::new(),
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 5, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn counts(days: &[(u32, u64)]) -> HashMap<NaiveDate, u64> {
+        days.iter().map(|(d, count)| (day(*d), *count)).collect()
+    }
+
+    #[test]
+    fn streaks_run_up_to_today_or_yesterday() {
+        let counts = counts(&[(1, 2), (2, 1), (3, 4), (5, 1), (6, 3)]);
+
+        assert_eq!(streaks(&counts, day(6)), (2, 3));
+        // Today isn't over, so not having worked yet doesn't break the streak
+        assert_eq!(streaks(&counts, day(7)), (2, 3));
+        // But a whole day without pomodoros does
+        assert_eq!(streaks(&counts, day(8)), (0, 3));
+    }
+
+    #[test]
+    fn streaks_leave_out_empty_and_later_days() {
+        let counts = counts(&[(1, 1), (2, 0), (3, 1), (4, 1), (10, 1), (11, 1), (12, 1)]);
+
+        assert_eq!(streaks(&counts, day(4)), (2, 2));
+        assert_eq!(streaks(&counts, day(2)), (1, 1));
+        assert_eq!(streaks(&HashMap::new(), day(4)), (0, 0));
+    }
+
+    #[test]
+    fn spreads_focus_over_the_hours_it_spanned() {
+        let records = [
+            // Half of it spent paused, from 10:40 to 11:20
+            record(Stage::Work, at(2, 10, 40), 40, 20 * 60),
+            // Across midnight, counted with the day it started
+            record(Stage::Work, at(2, 23, 45), 30, 30 * 60),
+            record(Stage::ShortBreak, at(2, 12, 0), 30, 30 * 60),
+            record(Stage::Work, at(4, 9, 0), 25, 25 * 60),
+        ];
+
+        let minutes = focused_minutes_by_hour(&records, day(1), day(3));
+
+        assert_eq!(minutes[10], 10);
+        assert_eq!(minutes[11], 10);
+        assert_eq!(minutes[23], 15);
+        assert_eq!(minutes[0], 15);
+        assert_eq!(minutes[12], 0);
+        assert_eq!(minutes[9], 0);
+        assert_eq!(minutes.iter().sum::<u64>(), 50);
+    }
+}
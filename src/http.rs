@@ -0,0 +1,403 @@
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{mpsc::Sender, Arc},
+    thread,
+};
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Server};
+
+use crate::control::{self, ControlCommand, Response};
+use crate::history::HistoryStore;
+
+/// Serves the timer over HTTP on localhost while alive, with JSON bodies:
+///
+/// - `GET /status`: the state of the timer
+/// - `GET /history?since=2024-05-01`: finished cycles, optionally since a date or RFC 3339 time
+/// - `POST /toggle`, `POST /finish`: run the command, answering with the new state
+/// - `POST /annotate` with `{"text": "phone call"}`: annotate the ongoing interruption
+///
+/// POST requests must be sent as `application/json`, which browsers don't allow
+/// web pages to do across origins without asking first, so that any page can't
+/// drive the timer. Requests must also be addressed to localhost, so that pages
+/// can't read the history either by rebinding their own domain to 127.0.0.1.
+pub struct HttpServer {
+    server: Arc<Server>,
+}
+
+#[derive(Deserialize)]
+struct Annotation {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct Error {
+    error: String,
+}
+
+impl HttpServer {
+    /// Starts listening on `127.0.0.1:port`, passing commands to `forward`
+    /// like the control socket does, and reading the history from `store`.
+    pub fn start<F>(port: u16, store: Option<HistoryStore>, forward: F) -> io::Result<HttpServer>
+    where
+        F: Fn(ControlCommand, Sender<Response>) -> bool + Send + 'static,
+    {
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let server = Server::http(address)
+            .map_err(|err| io::Error::other(format!("can't listen on {}: {}", address, err)))?;
+        // Port 0 leaves the choice of the port to the system
+        let port = server
+            .server_addr()
+            .to_ip()
+            .map_or(port, |address| address.port());
+        let server = Arc::new(server);
+
+        let incoming = Arc::clone(&server);
+        thread::spawn(move || {
+            let hosts = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+            for request in incoming.incoming_requests() {
+                serve(request, &hosts, store.as_ref(), &forward);
+            }
+        });
+
+        Ok(HttpServer { server })
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+/// Answers `request`, provided it is addressed to one of `hosts`.
+fn serve<F>(mut request: Request, hosts: &[String], store: Option<&HistoryStore>, forward: &F)
+where
+    F: Fn(ControlCommand, Sender<Response>) -> bool,
+{
+    let url = request.url().to_string();
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (url.as_str(), ""),
+    };
+
+    let is_json = request.headers().iter().any(|header| {
+        header.field.equiv("Content-Type") && header.value.as_str().starts_with("application/json")
+    });
+    let is_local = request.headers().iter().any(|header| {
+        header.field.equiv("Host")
+            && hosts
+                .iter()
+                .any(|host| header.value.as_str().eq_ignore_ascii_case(host))
+    });
+
+    let (code, body) = match (request.method(), path) {
+        _ if !is_local => error(403, "requests must be addressed to localhost"),
+        (Method::Get, "/status") => run(forward, ControlCommand::Status),
+        (Method::Get, "/history") => history(store, query),
+        (Method::Post, _) if !is_json => error(415, "POST requests must be application/json"),
+        (Method::Post, "/toggle") => run(forward, ControlCommand::Toggle),
+        (Method::Post, "/finish") => run(forward, ControlCommand::Finish),
+        (Method::Post, "/annotate") => {
+            let mut body = String::new();
+            match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => match serde_json::from_str::<Annotation>(&body) {
+                    Ok(annotation) => run(
+                        forward,
+                        ControlCommand::Annotate {
+                            text: annotation.text,
+                        },
+                    ),
+                    Err(err) => error(400, &format!("invalid annotation: {}", err)),
+                },
+                Err(err) => error(400, &err.to_string()),
+            }
+        }
+        (_, "/status") | (_, "/history") | (_, "/toggle") | (_, "/finish") | (_, "/annotate") => {
+            error(405, "method not allowed")
+        }
+        _ => error(404, "not found"),
+    };
+
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("the header is valid");
+    let response = tiny_http::Response::from_string(body)
+        .with_status_code(code)
+        .with_header(content_type);
+
+    // The client hanging up early is its own business
+    request.respond(response).unwrap_or_default();
+}
+
+fn run<F>(forward: &F, command: ControlCommand) -> (u16, String)
+where
+    F: Fn(ControlCommand, Sender<Response>) -> bool,
+{
+    match control::dispatch(forward, command) {
+        Response {
+            status: Some(status),
+            ..
+        } => json(200, &status),
+        Response { error: message, .. } => error(409, &message.unwrap_or_default()),
+    }
+}
+
+fn history(store: Option<&HistoryStore>, query: &str) -> (u16, String) {
+    let since = match query
+        .split('&')
+        .find_map(|param| param.strip_prefix("since="))
+        .map(parse_since)
+    {
+        Some(Ok(since)) => Some(since),
+        Some(Err(err)) => return error(400, &err),
+        None => None,
+    };
+
+    let records = match store.map(HistoryStore::load) {
+        Some(Ok(records)) => records,
+        Some(Err(err)) => return error(500, &err.to_string()),
+        None => Vec::new(),
+    };
+
+    let records: Vec<_> = records
+        .into_iter()
+        .filter(|record| since.map_or(true, |since| record.started_at >= since))
+        .collect();
+
+    json(200, &records)
+}
+
+/// Parses a date, taken as its local midnight, or an RFC 3339 time, as found in a query.
+fn parse_since(since: &str) -> Result<DateTime<Local>, String> {
+    let since = percent_decode_str(since)
+        .decode_utf8()
+        .map_err(|_| String::from("invalid `since`, expected UTF-8"))?;
+
+    if let Ok(date) = NaiveDate::parse_from_str(&since, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        if let Some(since) = Local.from_local_datetime(&midnight).earliest() {
+            return Ok(since);
+        }
+    }
+
+    DateTime::parse_from_rfc3339(&since)
+        .map(|since| since.with_timezone(&Local))
+        .map_err(|_| {
+            format!(
+                "invalid `since` {}, expected a date like 2024-05-01 or an RFC 3339 time",
+                since
+            )
+        })
+}
+
+fn json<T: Serialize>(code: u16, body: &T) -> (u16, String) {
+    match serde_json::to_string(body) {
+        Ok(body) => (code, body),
+        Err(err) => error(500, &err.to_string()),
+    }
+}
+
+fn error(code: u16, error: &str) -> (u16, String) {
+    let body = serde_json::to_string(&Error {
+        error: String::from(error),
+    })
+    .unwrap_or_default();
+
+    (code, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, sync::mpsc};
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::app::App;
+
+    /// A server over a fresh timer and a history of two cycles, on 2024-05-01 and 2024-05-03.
+    struct TestServer {
+        _server: HttpServer,
+        port: u16,
+        history_path: PathBuf,
+    }
+
+    impl TestServer {
+        fn start(name: &str) -> TestServer {
+            let history_path = std::env::temp_dir().join(format!("pomodoro-http-{}.jsonl", name));
+            fs::write(
+                &history_path,
+                [
+                    record("2024-05-01T08:00:00Z"),
+                    record("2024-05-03T12:00:00Z"),
+                ]
+                .join("\n"),
+            )
+            .unwrap();
+
+            let (commands, received) = mpsc::channel::<(ControlCommand, Sender<Response>)>();
+            thread::spawn(move || {
+                let mut app = App::default();
+                for (command, respond) in received {
+                    respond.send(control::handle(&mut app, command)).unwrap();
+                }
+            });
+
+            let server = HttpServer::start(
+                0,
+                Some(HistoryStore::new(history_path.clone())),
+                move |command, respond| commands.send((command, respond)).is_ok(),
+            )
+            .unwrap();
+            let port = server.server.server_addr().to_ip().unwrap().port();
+
+            TestServer {
+                _server: server,
+                port,
+                history_path,
+            }
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("http://127.0.0.1:{}{}", self.port, path)
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            fs::remove_file(&self.history_path).unwrap_or_default();
+        }
+    }
+
+    fn record(started_at: &str) -> String {
+        format!(
+            r#"{{"stage":"work","started_at":"{}","finished_at":"{}","completed":true,"focused_secs":1500,"paused_secs":0}}"#,
+            started_at, started_at
+        )
+    }
+
+    /// The status code and JSON body of the response to `request`.
+    fn send(request: ureq::Request, body: Option<&str>) -> (u16, Value) {
+        let response = match body {
+            Some(body) => request.send_string(body),
+            None => request.call(),
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(err) => panic!("{}", err),
+        };
+
+        let code = response.status();
+        (
+            code,
+            serde_json::from_str(&response.into_string().unwrap()).unwrap(),
+        )
+    }
+
+    fn history_since(server: &TestServer, since: &str) -> (u16, Vec<String>) {
+        let (code, body) = send(
+            ureq::get(&server.url(&format!("/history?since={}", since))),
+            None,
+        );
+        let started_at = body
+            .as_array()
+            .map(|records| {
+                records
+                    .iter()
+                    .map(|record| record["started_at"].as_str().unwrap().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        (code, started_at)
+    }
+
+    #[test]
+    fn runs_commands() {
+        let server = TestServer::start("commands");
+
+        let (code, status) = send(ureq::get(&server.url("/status")), None);
+        assert_eq!(code, 200);
+        assert_eq!(status["started"], false);
+
+        let (code, status) = send(
+            ureq::post(&server.url("/toggle")).set("Content-Type", "application/json"),
+            Some(""),
+        );
+        assert_eq!(code, 200);
+        assert_eq!(status["started"], true);
+
+        // There is no interruption to annotate while the timer runs
+        let (code, _) = send(
+            ureq::post(&server.url("/annotate")).set("Content-Type", "application/json"),
+            Some(r#"{"text": "phone call"}"#),
+        );
+        assert_eq!(code, 409);
+    }
+
+    #[test]
+    fn rejects_requests_it_does_not_serve() {
+        let server = TestServer::start("rejections");
+
+        let (code, _) = send(ureq::post(&server.url("/toggle")), Some(""));
+        assert_eq!(code, 415);
+        let (code, _) = send(
+            ureq::post(&server.url("/toggle")).set("Content-Type", "text/plain"),
+            Some(""),
+        );
+        assert_eq!(code, 415);
+
+        let (code, _) = send(ureq::delete(&server.url("/status")), None);
+        assert_eq!(code, 405);
+        let (code, _) = send(ureq::get(&server.url("/nothing")), None);
+        assert_eq!(code, 404);
+    }
+
+    #[test]
+    fn only_answers_requests_addressed_to_localhost() {
+        let server = TestServer::start("hosts");
+
+        let (code, _) = send(
+            ureq::get(&server.url("/history")).set("Host", "attacker.example"),
+            None,
+        );
+        assert_eq!(code, 403);
+        let (code, _) = send(
+            ureq::get(&server.url("/history")).set("Host", "127.0.0.1:1"),
+            None,
+        );
+        assert_eq!(code, 403);
+
+        let (code, _) = send(
+            ureq::get(&server.url("/history")).set("Host", &format!("localhost:{}", server.port)),
+            None,
+        );
+        assert_eq!(code, 200);
+    }
+
+    #[test]
+    fn filters_the_history() {
+        let server = TestServer::start("history");
+
+        let (code, all) = send(ureq::get(&server.url("/history")), None);
+        assert_eq!((code, all.as_array().unwrap().len()), (200, 2));
+
+        let (code, since_date) = history_since(&server, "2024-05-02");
+        assert_eq!(code, 200);
+        assert_eq!(since_date.len(), 1);
+
+        let (code, since_time) = history_since(&server, "2024-05-03T12%3A00%3A00%2B00%3A00");
+        assert_eq!(code, 200);
+        assert_eq!(since_time, since_date);
+
+        let (code, after_it) = history_since(&server, "2024-05-03T12%3a00%3a01Z");
+        assert_eq!((code, after_it.len()), (200, 0));
+
+        assert_eq!(history_since(&server, "yesterday").0, 400);
+        assert_eq!(history_since(&server, "%FF").0, 400);
+    }
+}
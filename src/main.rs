@@ -1,5 +1,16 @@
 mod app;
+mod compact;
+mod config;
+mod control;
+mod git;
+mod history;
+mod http;
+mod notifier;
 mod pomodoro_technique;
+mod stats;
+mod status_line;
+mod title;
+mod ui;
 mod widgets;
 
 use structopt::StructOpt;
@@ -7,6 +18,8 @@ use structopt::StructOpt;
 use std::{
     error::Error,
     io::{stdout, Write},
+    path::PathBuf,
+    process,
     time::Duration,
 };
 
@@ -15,22 +28,28 @@ use std::thread;
 
 use crossterm::{
     cursor,
-    event::{self, read, Event, KeyCode},
+    event::{
+        self, read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent,
+        KeyModifiers, MouseButton, MouseEvent,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
 };
 
-use tui::{
-    backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    text::{Span, Spans, Text},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
-    Terminal,
-};
+use tui::{backend::CrosstermBackend, Terminal};
 
-use crate::app::{App, AppView};
-use crate::widgets::Timer;
+use crate::app::{App, AppConfiguration, AppView};
+use crate::config::Config;
+use crate::control::{Client, ControlCommand, ControlServer, Response, Status};
+use crate::history::HistoryStore;
+use crate::http::HttpServer;
+use crate::notifier::{EventStreamNotifier, Notifiers};
+use crate::pomodoro_technique::Stage;
+use crate::status_line::{Output, StateFile, DEFAULT_STATUS_FORMAT};
+use crate::title::{TerminalTitle, DEFAULT_PAUSED_TITLE_FORMAT, DEFAULT_TITLE_FORMAT};
+use crate::ui::ClickableAreas;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "pomodoro")]
@@ -43,242 +62,336 @@ struct Pomodoro {
 
     #[structopt(short, long, default_value = "250")]
     tick_duration: u64,
+
+    /// Length of work stages, like `50m` or `1h30m`, in place of the config file's
+    #[structopt(long, parse(try_from_str = humantime::parse_duration))]
+    work: Option<Duration>,
+
+    /// Length of short breaks, like `10m`, in place of the config file's
+    #[structopt(long, parse(try_from_str = humantime::parse_duration))]
+    short_break: Option<Duration>,
+
+    /// Length of long breaks, like `30m`, in place of the config file's
+    #[structopt(long, parse(try_from_str = humantime::parse_duration))]
+    long_break: Option<Duration>,
+
+    /// Stages of one round, repeated over and over, in place of the config file's,
+    /// e.g. `work,short_break,work,long_break` or `w,s,w,l`
+    #[structopt(long, use_delimiter = true)]
+    sequence: Option<Vec<Stage>>,
+
+    /// Prints the plan of stages which the timer would follow, and exits
+    #[structopt(long)]
+    dry_run: bool,
+
+    /// Writes every change of state as a line of JSON to this file or FIFO,
+    /// or to stdout with `-`, which only works with `daemon`
+    #[structopt(long, parse(from_os_str))]
+    events: Option<PathBuf>,
+
+    /// Serves the status, the history and commands over HTTP on this port of localhost
+    #[structopt(long)]
+    http: Option<u16>,
+
+    /// Renders the timer on two lines, without taking over the whole terminal.
+    /// Turned on automatically when the terminal is too small for the regular layout
+    #[structopt(long)]
+    compact: bool,
+
+    /// Format of the terminal title; `{stage_icon}`, `{stage}`, `{remaining}`, `{paused}`,
+    /// `{interruptions}`, `{task}` and `{count}` are replaced by the current state
+    #[structopt(long, default_value = DEFAULT_TITLE_FORMAT)]
+    title_format: String,
+
+    /// Format of the terminal title while the timer is paused, with the same placeholders
+    #[structopt(long, default_value = DEFAULT_PAUSED_TITLE_FORMAT)]
+    paused_title_format: String,
+
+    /// Leaves the terminal title alone
+    #[structopt(long)]
+    no_title: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Prints statistics of the recorded history and exits
+    Stats {
+        /// Also prints a calendar heatmap of the last year
+        #[structopt(long)]
+        heatmap: bool,
+    },
+    /// Starts, pauses or resumes the timer of the running instance
+    Toggle,
+    /// Pauses the timer of the running instance
+    Pause,
+    /// Starts or resumes the timer of the running instance
+    Resume,
+    /// Finishes the current stage of the running instance
+    Finish,
+    /// Prints the state of the running instance, e.g. for a status bar
+    Status {
+        /// Prints a line like `🍅 12:34` instead, with the same placeholders as `--title-format`
+        #[structopt(long)]
+        format: Option<String>,
+        /// Keeps running and prints a new line whenever the state changes
+        #[structopt(long)]
+        watch: bool,
+        /// Prints JSON for a waybar custom module, with a tooltip and CSS classes
+        /// for the stage and whether the timer is running, paused or idle
+        #[structopt(long)]
+        waybar: bool,
+    },
+    /// Annotates the ongoing interruption of the running instance
+    Annotate { text: String },
+    /// Runs the timer in the background, without a terminal.
+    /// Instances started afterwards attach to it rather than running their own timer
+    Daemon,
 }
 
 enum TickContent {
-    KeyPress(crossterm::event::KeyEvent),
+    KeyPress(KeyEvent),
+    Paste(String),
+    Mouse(MouseEvent),
+    Control(ControlCommand, mpsc::Sender<Response>),
     None,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let opts = Pomodoro::from_args();
-    println!("{:#?}", opts);
 
-    enable_raw_mode()?;
+    if opts.verbose > 0 {
+        println!("{:#?}", opts);
+    }
 
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+    let control_command = match opts.command {
+        Some(Command::Stats { heatmap }) => {
+            let records = match HistoryStore::default_path() {
+                Some(path) => HistoryStore::new(path).load()?,
+                None => Vec::new(),
+            };
+            stats::print(&records, heatmap)?;
+            return Ok(());
+        }
+        Some(Command::Toggle) => Some(ControlCommand::Toggle),
+        Some(Command::Pause) => Some(ControlCommand::Pause),
+        Some(Command::Resume) => Some(ControlCommand::Resume),
+        Some(Command::Finish) => Some(ControlCommand::Finish),
+        Some(Command::Status {
+            ref format,
+            watch,
+            waybar,
+        }) => {
+            let output = match (waybar, format.clone()) {
+                (true, format) => {
+                    Output::Waybar(format.unwrap_or_else(|| String::from(DEFAULT_STATUS_FORMAT)))
+                }
+                (false, Some(format)) => Output::Format(format),
+                (false, None) => Output::Summary,
+            };
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+            if let Err(err) = status_line::print(&output, watch) {
+                eprintln!("pomodoro: {}", err);
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Annotate { ref text }) => {
+            Some(ControlCommand::Annotate { text: text.clone() })
+        }
+        Some(Command::Daemon) | None => None,
+    };
+
+    if let Some(command) = control_command {
+        let error = match Client::connect().and_then(|mut client| client.send(command)) {
+            Ok(Response {
+                error: Some(error), ..
+            }) => error,
+            Ok(_) => return Ok(()),
+            Err(err) => err.to_string(),
+        };
+
+        eprintln!("pomodoro: {}", error);
+        process::exit(1);
+    }
+
+    let mut config = match Config::default_path() {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+
+    let timer = &mut config.timer;
+    let overrides_timer = opts.work.is_some()
+        || opts.short_break.is_some()
+        || opts.long_break.is_some()
+        || opts.sequence.is_some();
+    timer.work = opts.work.or(timer.work);
+    timer.short_break = opts.short_break.or(timer.short_break);
+    timer.long_break = opts.long_break.or(timer.long_break);
+    timer.sequence = opts.sequence.clone().or_else(|| timer.sequence.take());
+    if let Err(err) = timer.validate() {
+        eprintln!("pomodoro: {}", err);
+        process::exit(1);
+    }
+
+    if opts.dry_run {
+        println!("{}", AppConfiguration::from_timer_config(&config.timer));
+        return Ok(());
+    }
+    let mut notifiers = Notifiers::from_config(&config);
+    if let Some(path) = opts.events.as_ref() {
+        let is_daemon = matches!(opts.command, Some(Command::Daemon));
+        if path.as_os_str() == "-" && !is_daemon {
+            eprintln!("pomodoro: --events - only works with `daemon`, as the UI takes up stdout");
+            process::exit(1);
+        }
+        notifiers.add(Box::new(EventStreamNotifier::open(path)?));
+    }
+    let mut app = App::new(
+        config,
+        HistoryStore::default_path().map(HistoryStore::new),
+        notifiers,
+    )?;
+
+    let tick_duration = Duration::from_millis(opts.tick_duration);
+
+    if let Some(Command::Daemon) = opts.command {
+        return run_daemon(app, tick_duration, opts.http);
+    }
 
     let (tx, rx) = mpsc::channel();
 
-    thread::spawn(move || loop {
-        if event::poll(Duration::from_millis(opts.tick_duration)).unwrap() {
-            if let Event::Key(key) = read().unwrap() {
-                tx.send(TickContent::KeyPress(key)).unwrap_or_default();
+    // Another instance owns the timer already, either a daemon or another terminal
+    let _control_server = match Client::connect() {
+        Ok(owner) => {
+            if overrides_timer {
+                eprintln!("warning: another instance owns the timer, so it keeps its own stages");
             }
+            app.attach(owner);
+            None
+        }
+        Err(_) => {
+            let control_tx = tx.clone();
+            // The timer still works without the control socket, only scripts can't reach it
+            ControlServer::start(move |command, respond| {
+                control_tx
+                    .send(TickContent::Control(command, respond))
+                    .is_ok()
+            })
+            .map_err(|err| eprintln!("warning: can't listen for commands: {}", err))
+            .ok()
+        }
+    };
+
+    let _http_server = match (opts.http, app.is_attached()) {
+        (Some(_), true) => {
+            eprintln!(
+                "warning: another instance owns the timer, so it serves HTTP itself if asked"
+            );
+            None
+        }
+        (Some(port), false) => {
+            let http_tx = tx.clone();
+            Some(HttpServer::start(
+                port,
+                HistoryStore::default_path().map(HistoryStore::new),
+                move |command, respond| {
+                    http_tx.send(TickContent::Control(command, respond)).is_ok()
+                },
+            )?)
         }
+        (None, _) => None,
+    };
 
-        tx.send(TickContent::None).unwrap_or_default();
-    });
+    // Only the instance owning the timer has a state worth publishing
+    let mut state_file = match app.is_attached() {
+        true => None,
+        false => StateFile::new().ok(),
+    };
 
-    let mut draw_borders = false;
-    let mut app = App::default();
+    let compact = opts.compact || terminal::size()?.1 < compact::MIN_REGULAR_LAYOUT_ROWS;
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
-            f.render_widget(Clear, size);
+    enable_raw_mode()?;
 
-            let red_block = Block::default()
-                .style(Style::default().fg(Color::Red))
-                .borders(Borders::ALL);
+    let mut stdout = stdout();
 
-            if draw_borders {
-                f.render_widget(red_block.clone(), size);
-            }
+    if compact {
+        compact::enter(&mut stdout)?;
+    } else {
+        execute!(
+            stdout,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            cursor::Hide
+        )?;
+    }
 
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(1)
-                .constraints(
-                    [
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(5),
-                        Constraint::Percentage(15),
-                        Constraint::Percentage(20),
-                    ]
-                    .as_ref(),
-                )
-                .split(size);
-
-            let pause_timer_area = chunks[2];
-            let pause_timer_area = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(
-                    [
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(40),
-                        Constraint::Percentage(30),
-                    ]
-                    .as_ref(),
-                )
-                .split(pause_timer_area)[1];
-
-            let pause_annotation_area = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(
-                    [
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(40),
-                        Constraint::Percentage(30),
-                    ]
-                    .as_ref(),
-                )
-                .split(chunks[3])[1];
-
-            if draw_borders {
-                f.render_widget(red_block.clone(), pause_timer_area);
-                f.render_widget(red_block.clone(), pause_annotation_area);
-            }
+    let mut title = match opts.no_title {
+        true => None,
+        false => Some(TerminalTitle::new(
+            &opts.title_format,
+            &opts.paused_title_format,
+        )),
+    };
+    if let Some(title) = title.as_ref() {
+        title.enter(&mut stdout)?;
+    }
 
-            if app.is_paused() {
-                let has_been_paused_for = app.get_pause_elapsed_time();
-
-                let minutes = has_been_paused_for / 60;
-                let seconds = has_been_paused_for % 60;
-
-                let span = Text::from(Span::from(String::from(format!(
-                    "{:02}:{:02}",
-                    minutes, seconds
-                ))));
-
-                let paragraph = Paragraph::new(span).alignment(Alignment::Center);
-                f.render_widget(paragraph, pause_timer_area);
-
-                match app.get_view() {
-                    AppView::AnnotationPopup => {
-                        f.render_widget(red_block.clone(), pause_annotation_area);
-
-                        match app.get_interruption_annotation() {
-                            Some(annotation) => {
-                                let annotation_length = annotation.len() as u16;
-                                let span = Span::from(annotation);
-                                let paragraph = Paragraph::new(span)
-                                    .block(red_block.clone())
-                                    .alignment(Alignment::Left);
-                                f.render_widget(paragraph, pause_annotation_area);
-                                f.set_cursor(
-                                    pause_annotation_area.x + 1 + annotation_length,
-                                    pause_annotation_area.y + 1,
-                                );
-                            }
-                            None => {}
-                        }
-                    }
-                    _ => {}
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    thread::spawn(move || loop {
+        if event::poll(tick_duration).unwrap() {
+            // Everything already waiting is read at once, so that pastes can be told apart
+            let mut keys = Vec::new();
+            loop {
+                match read().unwrap() {
+                    Event::Key(key) => keys.push(key),
+                    Event::Mouse(mouse) => tx.send(TickContent::Mouse(mouse)).unwrap_or_default(),
+                    Event::Resize(_, _) => {}
+                }
+
+                if !event::poll(Duration::from_millis(0)).unwrap() {
+                    break;
                 }
             }
 
-            let pomodoro_timer_area = chunks[1];
-            let pomodoro_timer_area = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(
-                    [
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(40),
-                        Constraint::Percentage(30),
-                    ]
-                    .as_ref(),
-                )
-                .split(pomodoro_timer_area)[1];
-
-            if draw_borders {
-                f.render_widget(red_block.clone(), pomodoro_timer_area);
+            for input in key_input(keys) {
+                tx.send(input).unwrap_or_default();
             }
+        }
 
-            let (is_due, remaining_time) = app.get_remaining_time();
+        tx.send(TickContent::None).unwrap_or_default();
+    });
 
-            if is_due {
-                app.finish_current_cycle();
-            }
+    let mut draw_borders = opts.debug;
+    let mut clickable_areas = ClickableAreas::default();
 
-            let clock = Timer::default()
-                .time_remaining(&remaining_time)
-                .borders(draw_borders)
-                .is_paused(app.is_paused())
-                .is_due(is_due);
-
-            f.render_widget(clock, pomodoro_timer_area);
-
-            match app.get_view() {
-                AppView::InterruptionsList => {
-                    let interruptions_panel = Layout::default()
-                        .direction(Direction::Vertical)
-                        .margin(1)
-                        .constraints(
-                            [
-                                Constraint::Percentage(20),
-                                Constraint::Percentage(60),
-                                Constraint::Percentage(20),
-                            ]
-                            .as_ref(),
-                        )
-                        .split(size);
-
-                    let interruptions_panel = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints(
-                            [
-                                Constraint::Percentage(40),
-                                Constraint::Percentage(20),
-                                Constraint::Percentage(40),
-                            ]
-                            .as_ref(),
-                        )
-                        .split(interruptions_panel[1])[1];
-
-                    if draw_borders {
-                        f.render_widget(red_block.clone(), interruptions_panel);
-                    }
+    loop {
+        app.tick();
 
-                    let interruptions: Vec<ListItem> = app
-                        .get_interruption_history()
-                        .iter()
-                        .map(|interruption| {
-                            let duration = (interruption.finished_at.unwrap()
-                                - interruption.started_at)
-                                .as_secs();
-                            let annotation = interruption
-                                .annotation
-                                .as_ref()
-                                .map_or(String::from(""), |annotation| annotation.to_string());
-
-                            ListItem::new(Spans::from(vec![
-                                Span::from(duration.to_string()),
-                                Span::raw(" "),
-                                Span::from(annotation),
-                            ]))
-                        })
-                        .collect();
-
-                    let interruptions_list = List::new(interruptions)
-                        .block(
-                            Block::default()
-                                .title(Span::from("INTERRUPTIONS"))
-                                .borders(Borders::ALL)
-                                .border_type(BorderType::Rounded),
-                        )
-                        .highlight_style(Style::default().add_modifier(Modifier::ITALIC));
-
-                    f.render_widget(Clear, interruptions_panel);
-                    f.render_widget(interruptions_list, interruptions_panel);
-                }
-                _ => {}
-            }
-        })?;
+        if let Some(state_file) = state_file.as_mut() {
+            state_file
+                .update(&Status::from_app(&app))
+                .unwrap_or_default();
+        }
+
+        if compact {
+            compact::draw(terminal.backend_mut(), &app)?;
+        } else {
+            terminal.draw(|f| clickable_areas = ui::draw(f, &app, draw_borders))?;
+        }
+
+        if let Some(title) = title.as_mut() {
+            title.update(terminal.backend_mut(), &app)?;
+        }
 
         match rx.recv()? {
             TickContent::KeyPress(key_event) => match app.get_view() {
                 AppView::Normal => match key_event.code {
-                    KeyCode::Char('q') => {
-                        disable_raw_mode()?;
-                        execute!(terminal.backend_mut(), cursor::Show, LeaveAlternateScreen)?;
-                        break;
-                    }
+                    KeyCode::Char('q') => break,
                     KeyCode::Char('c') => {
                         app.finish_current_cycle();
                     }
@@ -286,32 +399,258 @@ fn main() -> Result<(), Box<dyn Error>> {
                         draw_borders = !draw_borders;
                     }
                     KeyCode::Char(' ') => {
-                        app.toggle_timer();
+                        app.toggle_timer_and_annotate();
                     }
                     KeyCode::Char('i') => {
                         app.change_view(AppView::InterruptionsList);
                     }
+                    KeyCode::Char('t') => {
+                        app.change_view(AppView::TaskPrompt);
+                    }
+                    KeyCode::Char('h') => {
+                        app.change_view(AppView::History);
+                    }
+                    KeyCode::Char('g') => {
+                        app.change_view(AppView::Charts);
+                    }
+                    KeyCode::Char('y') => {
+                        app.change_view(AppView::Heatmap);
+                    }
                     _ => {}
                 },
                 AppView::AnnotationPopup => match key_event.code {
-                    KeyCode::Char(c) => {
-                        app.append_to_interruption_annotation(c);
-                    }
-                    KeyCode::Backspace => {
-                        app.pop_from_interruption_annotation();
+                    KeyCode::Enter => {
+                        app.submit_interruption_annotation();
                     }
+                    _ => app.edit_interruption_annotation(key_event),
+                },
+                AppView::TaskPrompt => match key_event.code {
                     KeyCode::Enter => {
+                        app.save_task();
                         app.change_view(AppView::Normal);
                     }
-                    _ => {}
+                    KeyCode::Esc => {
+                        app.change_view(AppView::Normal);
+                    }
+                    _ => app.edit_task(key_event),
                 },
                 AppView::InterruptionsList => match key_event.code {
+                    KeyCode::Down => app.select_next_interruption(),
+                    KeyCode::Up => app.select_previous_interruption(),
+                    _ => app.close_interruptions_list(),
+                },
+                AppView::History => match key_event.code {
+                    KeyCode::Down => app.select_next_cycle(),
+                    KeyCode::Up => app.select_previous_cycle(),
+                    KeyCode::Enter => app.inspect_selected_cycle(),
+                    _ => app.change_view(AppView::Normal),
+                },
+                AppView::Heatmap => app.change_view(AppView::Normal),
+                AppView::Charts => match key_event.code {
+                    KeyCode::Left => app.show_earlier_charts(),
+                    KeyCode::Right => app.show_later_charts(),
                     _ => app.change_view(AppView::Normal),
                 },
             },
+            TickContent::Paste(text) => app.paste(&text),
+            TickContent::Mouse(mouse_event) => match mouse_event {
+                MouseEvent::Down(MouseButton::Left, column, row, _) => {
+                    if clickable_areas.is_outside_popup(column, row) {
+                        match app.get_view() {
+                            AppView::InterruptionsList => app.close_interruptions_list(),
+                            AppView::AnnotationPopup => app.submit_interruption_annotation(),
+                            _ => app.change_view(AppView::Normal),
+                        }
+                    } else if let AppView::Normal = app.get_view() {
+                        if clickable_areas.is_on_timer(column, row) {
+                            app.toggle_timer_and_annotate();
+                        }
+                    }
+                }
+                MouseEvent::ScrollDown(_, _, _) => match app.get_view() {
+                    AppView::InterruptionsList => app.select_next_interruption(),
+                    AppView::History => app.select_next_cycle(),
+                    _ => {}
+                },
+                MouseEvent::ScrollUp(_, _, _) => match app.get_view() {
+                    AppView::InterruptionsList => app.select_previous_interruption(),
+                    AppView::History => app.select_previous_cycle(),
+                    _ => {}
+                },
+                _ => {}
+            },
+            TickContent::Control(command, respond) => {
+                respond
+                    .send(control::handle(&mut app, command))
+                    .unwrap_or_default();
+            }
             TickContent::None => {}
         }
     }
 
+    disable_raw_mode()?;
+
+    if let Some(title) = title.as_ref() {
+        title.leave(terminal.backend_mut())?;
+    }
+
+    if compact {
+        compact::leave(terminal.backend_mut())?;
+    } else {
+        execute!(
+            terminal.backend_mut(),
+            cursor::Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        )?;
+    }
+
+    app.finish_commit_lookups();
+
     Ok(())
 }
+
+/// Runs the timer without a terminal, for clients to attach to,
+/// until it gets killed.
+fn run_daemon(
+    mut app: App,
+    tick_duration: Duration,
+    http_port: Option<u16>,
+) -> Result<(), Box<dyn Error>> {
+    let (tx, rx) = mpsc::channel();
+
+    let http_tx = tx.clone();
+    let _control_server =
+        ControlServer::start(move |command, respond| tx.send((command, respond)).is_ok())?;
+    let _http_server = match http_port {
+        Some(port) => Some(HttpServer::start(
+            port,
+            HistoryStore::default_path().map(HistoryStore::new),
+            move |command, respond| http_tx.send((command, respond)).is_ok(),
+        )?),
+        None => None,
+    };
+    let mut state_file = StateFile::new()?;
+
+    loop {
+        match rx.recv_timeout(tick_duration) {
+            Ok((command, respond)) => {
+                respond
+                    .send(control::handle(&mut app, command))
+                    .unwrap_or_default();
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                app.finish_commit_lookups();
+                return Ok(());
+            }
+        }
+
+        app.tick();
+        state_file
+            .update(&Status::from_app(&app))
+            .unwrap_or_default();
+    }
+}
+
+/// How many keys read at once make a paste, even without a line break.
+const MIN_PASTE_LENGTH: usize = 8;
+
+/// Turns the key presses read at once into input for the main loop.
+///
+/// crossterm doesn't report pastes as such, but typing comes one key press at a
+/// time while a paste comes as many, all of them text. Those are passed on as a
+/// paste, so that a pasted line break doesn't submit a prompt and the rest of
+/// the paste doesn't reach the shortcuts of the view shown next. Fast typing
+/// can also bring a few keys at once, e.g. "x⏎", so only a long run of text,
+/// or text going on past a line break, is taken for a paste.
+fn key_input(keys: Vec<KeyEvent>) -> Vec<TickContent> {
+    let text: Option<String> = match keys.len() {
+        0 | 1 => None,
+        _ => keys
+            .iter()
+            .map(|key| match key.code {
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => Some(c),
+                KeyCode::Enter => Some('\n'),
+                KeyCode::Tab => Some('\t'),
+                _ => None,
+            })
+            .collect(),
+    };
+    let pasted = text.filter(|text| {
+        text.chars().count() >= MIN_PASTE_LENGTH || text.trim_end_matches('\n').contains('\n')
+    });
+
+    match pasted {
+        Some(text) => vec![TickContent::Paste(text)],
+        None => keys.into_iter().map(TickContent::KeyPress).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(text: &str) -> Vec<KeyEvent> {
+        text.chars()
+            .map(|c| match c {
+                '\n' => KeyEvent::from(KeyCode::Enter),
+                c => KeyEvent::from(KeyCode::Char(c)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pasting_a_line_break_into_the_popup_leaves_the_timer_alone() {
+        let mut app = App::default();
+        app.toggle_timer_and_annotate();
+        app.toggle_timer_and_annotate();
+        assert_eq!(app.get_view(), &AppView::AnnotationPopup);
+
+        for input in key_input(keys("a\nqc")) {
+            match input {
+                TickContent::Paste(text) => app.paste(&text),
+                _ => panic!("the paste was taken for key presses"),
+            }
+        }
+
+        assert_eq!(app.get_view(), &AppView::AnnotationPopup);
+        assert_eq!(app.get_annotation_input().value(), "a qc");
+        assert!(app.is_paused());
+        assert!(app.get_todays_history().is_empty());
+    }
+
+    #[test]
+    fn passes_typing_and_shortcuts_on_as_key_presses() {
+        let single = key_input(keys("q"));
+        assert!(matches!(single.as_slice(), [TickContent::KeyPress(_)]));
+
+        // Typed quickly enough to be read at once
+        for typed in ["x\n", "jjj", "qc"].iter() {
+            let input = key_input(keys(typed));
+            assert_eq!(input.len(), typed.len(), "{:?}", typed);
+            assert!(input
+                .iter()
+                .all(|input| matches!(input, TickContent::KeyPress(_))));
+        }
+
+        let mut arrows = vec![KeyEvent::from(KeyCode::Down); 3];
+        arrows.push(KeyEvent::from(KeyCode::Char('x')));
+        assert_eq!(
+            key_input(arrows)
+                .iter()
+                .filter(|input| matches!(input, TickContent::KeyPress(_)))
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn takes_long_runs_of_text_for_pastes() {
+        let pasted = key_input(keys("phone call"));
+        assert!(matches!(pasted.as_slice(), [TickContent::Paste(text)] if text == "phone call"));
+
+        let pasted = key_input(keys("ab\ncd\n"));
+        assert!(matches!(pasted.as_slice(), [TickContent::Paste(text)] if text == "ab\ncd\n"));
+    }
+}
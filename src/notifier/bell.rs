@@ -0,0 +1,26 @@
+use std::io::{self, Write};
+
+use super::{terminal_stdout, Event, Notifier};
+
+/// Rings the terminal bell, which works even without an audio device.
+#[derive(Default)]
+pub struct BellNotifier;
+
+impl Notifier for BellNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        match event {
+            Event::StageFinished { .. } | Event::StageEnding { .. } | Event::LongPause { .. } => {
+                let mut stdout = terminal_stdout()?;
+                stdout.write_all(b"\x07")?;
+                stdout.flush()
+            }
+            Event::StageStarted { .. }
+            | Event::PauseStarted { .. }
+            | Event::Resumed { .. }
+            | Event::InterruptionAnnotated { .. }
+            | Event::TimerRunning { .. }
+            | Event::AnnotationUpdated { .. }
+            | Event::ViewChanged { .. } => Ok(()),
+        }
+    }
+}
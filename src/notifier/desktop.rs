@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use zbus::{blocking::Connection, zvariant::Value};
+
+use super::{message, Event, Notifier};
+
+const NOTIFICATIONS_BUS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+const APP_NAME: &str = "pomodoro";
+
+/// How long to wait for the notification server before falling back on the next notifier.
+const REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Notifies the user about stage transitions even when the terminal is hidden,
+/// through the desktop's notification server on the D-Bus session bus.
+///
+/// D-Bus is spoken from a thread of its own, so that a stuck notification server
+/// holds up the timer for `REPLY_TIMEOUT` at most, and only until it answers again.
+pub struct DesktopNotifier {
+    requests: Sender<(String, String)>,
+    replies: Receiver<zbus::Result<()>>,
+    /// Whether the server has yet to answer a notification which timed out
+    waiting_on_server: bool,
+}
+
+impl Default for DesktopNotifier {
+    fn default() -> DesktopNotifier {
+        DesktopNotifier::new(Connection::session)
+    }
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        match message(event) {
+            Some((summary, body)) => self.notify_over_dbus(summary, body),
+            None => Ok(()),
+        }
+    }
+}
+
+impl DesktopNotifier {
+    /// Sends notifications over the bus `connect` connects to.
+    pub fn new<C>(connect: C) -> DesktopNotifier
+    where
+        C: FnOnce() -> zbus::Result<Connection> + Send + 'static,
+    {
+        let (requests, pending_requests) = mpsc::channel::<(String, String)>();
+        let (send_reply, replies) = mpsc::channel();
+
+        thread::spawn(move || {
+            let connection = connect();
+            let mut last_id = 0;
+
+            for (summary, body) in pending_requests {
+                let reply = match connection.as_ref() {
+                    Ok(connection) => {
+                        send_notification(connection, last_id, &summary, &body).map(|id| {
+                            last_id = id;
+                        })
+                    }
+                    Err(_) => Err(zbus::Error::Unsupported),
+                };
+
+                if send_reply.send(reply).is_err() {
+                    return;
+                }
+            }
+        });
+
+        DesktopNotifier {
+            requests,
+            replies,
+            waiting_on_server: false,
+        }
+    }
+
+    fn notify_over_dbus(&mut self, summary: String, body: String) -> io::Result<()> {
+        if self.waiting_on_server {
+            match self.replies.try_recv() {
+                Ok(_) => self.waiting_on_server = false,
+                Err(TryRecvError::Empty) => return Err(not_answering()),
+                Err(TryRecvError::Disconnected) => return Err(disconnected()),
+            }
+        }
+
+        self.requests
+            .send((summary, body))
+            .map_err(|_| disconnected())?;
+
+        match self.replies.recv_timeout(REPLY_TIMEOUT) {
+            Ok(reply) => reply.map_err(io::Error::other),
+            Err(RecvTimeoutError::Timeout) => {
+                self.waiting_on_server = true;
+                Err(not_answering())
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(disconnected()),
+        }
+    }
+}
+
+/// Shows a notification replacing the one with `replaces_id`, returning the id of the new one.
+fn send_notification(
+    connection: &Connection,
+    replaces_id: u32,
+    summary: &str,
+    body: &str,
+) -> zbus::Result<u32> {
+    let actions: Vec<&str> = Vec::new();
+    let hints: HashMap<&str, Value> = HashMap::new();
+    let expire_timeout: i32 = -1;
+
+    let reply = connection.call_method(
+        Some(NOTIFICATIONS_BUS_NAME),
+        NOTIFICATIONS_PATH,
+        Some(NOTIFICATIONS_BUS_NAME),
+        "Notify",
+        &(
+            APP_NAME,
+            replaces_id,
+            "",
+            summary,
+            body,
+            actions,
+            hints,
+            expire_timeout,
+        ),
+    )?;
+
+    reply.body().deserialize()
+}
+
+fn not_answering() -> io::Error {
+    io::Error::new(
+        ErrorKind::TimedOut,
+        "the notification server isn't answering",
+    )
+}
+
+fn disconnected() -> io::Error {
+    io::Error::other("the D-Bus thread has exited")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use zbus::blocking::connection;
+
+    use super::*;
+    use crate::pomodoro_technique::Stage;
+
+    /// A private session bus, shut down once dropped.
+    struct SessionBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl SessionBus {
+        /// Starts a bus, unless `dbus-daemon` isn't installed.
+        fn start() -> Option<SessionBus> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+
+            Some(SessionBus {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+
+        fn connect(&self) -> impl FnOnce() -> zbus::Result<Connection> + Send + 'static {
+            let address = self.address.clone();
+            move || connection::Builder::address(address.as_str())?.build()
+        }
+    }
+
+    impl Drop for SessionBus {
+        fn drop(&mut self) {
+            self.daemon.kill().unwrap_or_default();
+            self.daemon.wait().map(|_| ()).unwrap_or_default();
+        }
+    }
+
+    /// The replaced id, summary and body of each notification received.
+    type Received = Arc<Mutex<Vec<(u32, String, String)>>>;
+
+    /// A notification server recording the notifications it is sent,
+    /// after taking `delay` to answer each one.
+    struct NotificationServer {
+        notifications: Received,
+        delay: Duration,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl NotificationServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            replaces_id: u32,
+            _app_icon: &str,
+            summary: &str,
+            body: &str,
+            _actions: Vec<&str>,
+            _hints: HashMap<&str, Value<'_>>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            thread::sleep(self.delay);
+
+            let mut notifications = self.notifications.lock().unwrap();
+            notifications.push((replaces_id, summary.to_string(), body.to_string()));
+            notifications.len() as u32
+        }
+    }
+
+    fn serve(bus: &SessionBus, delay: Duration) -> (Connection, Received) {
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        let server = NotificationServer {
+            notifications: Arc::clone(&notifications),
+            delay,
+        };
+
+        let connection = connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name(NOTIFICATIONS_BUS_NAME)
+            .unwrap()
+            .serve_at(NOTIFICATIONS_PATH, server)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        (connection, notifications)
+    }
+
+    fn stage_finished() -> Event {
+        Event::StageFinished {
+            stage: Stage::Work,
+            completed: true,
+            next_stage: Stage::ShortBreak,
+            next_duration: Duration::from_secs(5 * 60),
+            daily_goal_reached: false,
+        }
+    }
+
+    #[test]
+    fn each_notification_replaces_the_last_one() {
+        let bus = match SessionBus::start() {
+            Some(bus) => bus,
+            None => return,
+        };
+        let (_server, notifications) = serve(&bus, Duration::from_millis(0));
+
+        let mut notifier = DesktopNotifier::new(bus.connect());
+        notifier.notify(&stage_finished()).unwrap();
+        notifier.notify(&stage_finished()).unwrap();
+
+        let notifications = notifications.lock().unwrap();
+        let (summary, body) = message(&stage_finished()).unwrap();
+        assert_eq!(
+            *notifications,
+            vec![(0, summary.clone(), body.clone()), (1, summary, body)]
+        );
+    }
+
+    #[test]
+    fn gives_up_on_a_stuck_server_without_holding_up_the_timer() {
+        let bus = match SessionBus::start() {
+            Some(bus) => bus,
+            None => return,
+        };
+        let (_server, _) = serve(&bus, Duration::from_secs(3));
+
+        let mut notifier = DesktopNotifier::new(bus.connect());
+
+        let started_at = Instant::now();
+        let error = notifier.notify(&stage_finished()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert!(started_at.elapsed() < Duration::from_secs(2));
+
+        // While the server is still busy with the first one, later ones fail right away
+        let started_at = Instant::now();
+        assert!(notifier.notify(&stage_finished()).is_err());
+        assert!(started_at.elapsed() < REPLY_TIMEOUT);
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    os::unix::fs::{FileTypeExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use super::{Event, Notifier};
+
+/// Writes every event as a line of JSON, for other tools to follow the session
+/// or to keep as a log, e.g.
+/// `{"seq":3,"time":"2024-05-02T10:12:00+02:00","event":"pause_started","stage":"work","remaining_secs":812}`.
+pub struct EventStreamNotifier {
+    out: Option<Box<dyn Write>>,
+    /// The FIFO written to, reopened whenever a new reader may have come along
+    fifo: Option<PathBuf>,
+    /// Number of the next event, starting from 1, so readers can tell if they missed any
+    seq: u64,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    seq: u64,
+    time: DateTime<Local>,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+impl EventStreamNotifier {
+    /// Appends the events to the file at `path`, which may as well be a FIFO,
+    /// or writes them to stdout when `path` is `-`.
+    ///
+    /// A FIFO is written to without blocking, so that the timer doesn't wait
+    /// for a reader: events are dropped while there is none, or it lags behind.
+    pub fn open(path: &Path) -> io::Result<EventStreamNotifier> {
+        let in_context =
+            |err: io::Error| io::Error::new(err.kind(), format!("{}: {}", path.display(), err));
+
+        let is_fifo = match fs::metadata(path) {
+            Ok(metadata) => metadata.file_type().is_fifo(),
+            Err(_) => false,
+        };
+
+        let mut notifier = EventStreamNotifier {
+            out: None,
+            fifo: None,
+            seq: 1,
+        };
+        match (path.to_str(), is_fifo) {
+            (Some("-"), _) => notifier.out = Some(Box::new(io::stdout())),
+            (_, true) => {
+                notifier.out = open_fifo(path).map_err(in_context)?;
+                notifier.fifo = Some(path.to_path_buf());
+            }
+            (_, false) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(in_context)?;
+                notifier.out = Some(Box::new(file));
+            }
+        }
+
+        Ok(notifier)
+    }
+}
+
+impl Notifier for EventStreamNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        let line = Line {
+            seq: self.seq,
+            time: Local::now(),
+            event,
+        };
+        // Numbered even when it can't be written, e.g. once a FIFO's reader is gone
+        self.seq += 1;
+
+        if let (None, Some(fifo)) = (self.out.as_ref(), self.fifo.as_ref()) {
+            self.out = open_fifo(fifo)?;
+        }
+        let out = match self.out.as_mut() {
+            Some(out) => out,
+            None => return Ok(()),
+        };
+
+        let written = writeln!(out, "{}", serde_json::to_string(&line)?).and_then(|_| out.flush());
+        match written {
+            // The reader is gone; the next one gets the events from then on
+            Err(err) if err.kind() == ErrorKind::BrokenPipe && self.fifo.is_some() => {
+                self.out = None;
+                Ok(())
+            }
+            written => written,
+        }
+    }
+}
+
+/// Opens the FIFO at `path` for writing, unless nothing is reading from it yet.
+fn open_fifo(path: &Path) -> io::Result<Option<Box<dyn Write>>> {
+    let opened = OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path);
+
+    match opened {
+        Ok(fifo) => Ok(Some(Box::new(fifo) as Box<dyn Write>)),
+        Err(err) if err.raw_os_error() == Some(libc::ENXIO) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::CString,
+        io::{BufRead, BufReader},
+        os::unix::ffi::OsStrExt,
+    };
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::app::AppView;
+
+    fn view_changed() -> Event {
+        Event::ViewChanged {
+            view: AppView::History,
+        }
+    }
+
+    #[test]
+    fn writes_to_a_fifo_only_while_it_is_read() {
+        let dir = std::env::temp_dir().join(format!("pomodoro-events-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events");
+        let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        // Neither opening it nor sending events waits for a reader
+        let mut notifier = EventStreamNotifier::open(&path).unwrap();
+        notifier.notify(&view_changed()).unwrap();
+
+        let reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+        notifier.notify(&view_changed()).unwrap();
+
+        let mut line = String::new();
+        BufReader::new(&reader).read_line(&mut line).unwrap();
+        let line: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(line["seq"], 2);
+        assert_eq!(line["event"], "view_changed");
+
+        // Once the reader is gone, events are dropped again
+        drop(reader);
+        notifier.notify(&view_changed()).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap_or_default();
+    }
+}
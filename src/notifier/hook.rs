@@ -0,0 +1,155 @@
+use std::{
+    io,
+    os::unix::process::CommandExt,
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{Event, Notifier};
+use crate::config::HooksConfig;
+
+/// How often a running hook is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs the user's shell commands configured for each event, in the background.
+///
+/// Every hook gets `POMODORO_EVENT` and `POMODORO_STAGE`, plus, depending on the event:
+///
+/// - `POMODORO_DURATION`: seconds the started stage lasts
+/// - `POMODORO_NEXT_STAGE` and `POMODORO_NEXT_DURATION`: the stage following the finished one
+/// - `POMODORO_REMAINING`: seconds left in the stage when it was paused or resumed
+/// - `POMODORO_PAUSED_FOR`: seconds the timer was paused for, once resumed
+/// - `POMODORO_ANNOTATION`: the annotation of the interruption
+///
+/// Hooks have their output discarded, as it would garble the UI,
+/// and get killed once they run for longer than the configured timeout.
+pub struct HookNotifier {
+    hooks: HooksConfig,
+}
+
+impl HookNotifier {
+    pub fn new(hooks: HooksConfig) -> HookNotifier {
+        HookNotifier { hooks }
+    }
+
+    fn run(&self, event: &str, command: Option<&String>, env: &[(&str, String)]) -> io::Result<()> {
+        let command = match command {
+            Some(command) => command,
+            None => return Ok(()),
+        };
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("POMODORO_EVENT", event)
+            .envs(env.iter().map(|(name, value)| (name, value)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            // Lets the whole hook be killed, rather than only the shell running it
+            .process_group(0)
+            .spawn()?;
+
+        let timeout = Duration::from_secs(self.hooks.timeout);
+        thread::spawn(move || wait_or_kill(child, timeout));
+
+        Ok(())
+    }
+}
+
+impl Notifier for HookNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        match event {
+            Event::StageStarted { stage, duration } => self.run(
+                "stage_started",
+                self.hooks.stage_started.as_ref(),
+                &[
+                    ("POMODORO_STAGE", stage.as_str().to_string()),
+                    ("POMODORO_DURATION", duration.as_secs().to_string()),
+                ],
+            ),
+            Event::StageFinished {
+                stage,
+                next_stage,
+                next_duration,
+                daily_goal_reached,
+                ..
+            } => {
+                let env = [
+                    ("POMODORO_STAGE", stage.as_str().to_string()),
+                    ("POMODORO_NEXT_STAGE", next_stage.as_str().to_string()),
+                    (
+                        "POMODORO_NEXT_DURATION",
+                        next_duration.as_secs().to_string(),
+                    ),
+                ];
+                self.run("stage_finished", self.hooks.stage_finished.as_ref(), &env)?;
+
+                match daily_goal_reached {
+                    true => self.run(
+                        "daily_goal_reached",
+                        self.hooks.daily_goal_reached.as_ref(),
+                        &env,
+                    ),
+                    false => Ok(()),
+                }
+            }
+            Event::PauseStarted { stage, remaining } => self.run(
+                "paused",
+                self.hooks.paused.as_ref(),
+                &[
+                    ("POMODORO_STAGE", stage.as_str().to_string()),
+                    ("POMODORO_REMAINING", remaining.as_secs().to_string()),
+                ],
+            ),
+            Event::Resumed {
+                stage,
+                remaining,
+                paused_for,
+            } => self.run(
+                "resumed",
+                self.hooks.resumed.as_ref(),
+                &[
+                    ("POMODORO_STAGE", stage.as_str().to_string()),
+                    ("POMODORO_REMAINING", remaining.as_secs().to_string()),
+                    ("POMODORO_PAUSED_FOR", paused_for.as_secs().to_string()),
+                ],
+            ),
+            Event::InterruptionAnnotated { stage, annotation } => self.run(
+                "annotation_saved",
+                self.hooks.annotation_saved.as_ref(),
+                &[
+                    ("POMODORO_STAGE", stage.as_str().to_string()),
+                    ("POMODORO_ANNOTATION", annotation.clone()),
+                ],
+            ),
+            Event::StageEnding { .. }
+            | Event::LongPause { .. }
+            | Event::TimerRunning { .. }
+            | Event::AnnotationUpdated { .. }
+            | Event::ViewChanged { .. } => Ok(()),
+        }
+    }
+}
+
+fn wait_or_kill(mut child: Child, timeout: Duration) {
+    let started_at = Instant::now();
+
+    loop {
+        match child.try_wait() {
+            Ok(None) if started_at.elapsed() < timeout => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                Command::new("kill")
+                    .args(["-KILL", "--", &format!("-{}", child.id())])
+                    .status()
+                    .map(|_| ())
+                    .unwrap_or_default();
+                child.kill().unwrap_or_default();
+                child.wait().map(|_| ()).unwrap_or_default();
+                return;
+            }
+            Ok(Some(_)) | Err(_) => return,
+        }
+    }
+}
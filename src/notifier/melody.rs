@@ -0,0 +1,92 @@
+use std::{convert::TryFrom, time::Duration};
+
+use rodio::{
+    source::{self, SineWave, Zero},
+    Source,
+};
+use serde::Deserialize;
+
+use crate::config::MelodyNote;
+
+const SAMPLE_RATE: u32 = 48000;
+/// Keeps each note from starting with a click.
+const FADE_IN: Duration = Duration::from_millis(5);
+
+/// Pitches sounding together, parsed from note names such as `"C5"`, `"F#4 A4"`
+/// or `"rest"`, with the octave in scientific pitch notation.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Chord {
+    frequencies: Vec<u32>,
+}
+
+impl TryFrom<String> for Chord {
+    type Error = String;
+
+    fn try_from(notes: String) -> Result<Chord, String> {
+        let frequencies = notes
+            .split_whitespace()
+            .filter(|note| !note.eq_ignore_ascii_case("rest"))
+            .map(|note| frequency(note).ok_or_else(|| format!("invalid note `{}`", note)))
+            .collect::<Result<Vec<u32>, String>>()?;
+
+        Ok(Chord { frequencies })
+    }
+}
+
+/// Frequency in Hz of a note name, e.g. 440 for `"A4"`.
+fn frequency(note: &str) -> Option<u32> {
+    let mut chars = note.chars();
+
+    let semitone: i32 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+
+    let rest = chars.as_str();
+    let (semitone, octave) = match rest.chars().next()? {
+        '#' => (semitone + 1, &rest[1..]),
+        'b' => (semitone - 1, &rest[1..]),
+        _ => (semitone, rest),
+    };
+    let octave: i32 = octave
+        .parse()
+        .ok()
+        .filter(|octave| (0..=9).contains(octave))?;
+
+    let semitones_from_a4 = (octave - 4) * 12 + semitone - 9;
+    let frequency = 440.0 * 2f32.powf(semitones_from_a4 as f32 / 12.0);
+
+    Some(frequency.round() as u32)
+}
+
+/// Synthesizes `notes` one after the other, each chord mixed from sine waves.
+pub fn melody(notes: &[MelodyNote]) -> impl Source<Item = f32> + Send {
+    let notes: Vec<Box<dyn Source<Item = f32> + Send>> = notes
+        .iter()
+        .map(|note| chord(&note.note, Duration::from_secs_f32(note.duration)))
+        .collect();
+
+    source::from_iter(notes)
+}
+
+fn chord(chord: &Chord, duration: Duration) -> Box<dyn Source<Item = f32> + Send> {
+    let silence: Box<dyn Source<Item = f32> + Send> =
+        Box::new(Zero::<f32>::new(1, SAMPLE_RATE).take_duration(duration));
+    // Keeps the mix of several pitches as loud as a single one
+    let amplitude = 1.0 / chord.frequencies.len().max(1) as f32;
+
+    chord.frequencies.iter().fold(silence, |mixed, &frequency| {
+        let tone = SineWave::new(frequency)
+            .take_duration(duration)
+            .amplify(amplitude)
+            .fade_in(FADE_IN);
+        Box::new(mixed.mix(tone))
+    })
+}
@@ -0,0 +1,290 @@
+mod bell;
+mod desktop;
+mod event_stream;
+mod hook;
+mod melody;
+#[cfg(test)]
+mod recording;
+mod sound;
+mod terminal;
+mod webhook;
+
+use std::{
+    io::{self, ErrorKind, IsTerminal},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::app::AppView;
+use crate::config::Config;
+use crate::pomodoro_technique::Stage;
+
+pub use bell::BellNotifier;
+pub use desktop::DesktopNotifier;
+pub use event_stream::EventStreamNotifier;
+pub use hook::HookNotifier;
+pub use melody::Chord;
+#[cfg(test)]
+pub use recording::RecordingNotifier;
+pub use sound::SoundNotifier;
+pub use terminal::TerminalNotifier;
+pub use webhook::{WebhookNotifier, WEBHOOK_EVENTS};
+
+/// What the app tells its notifiers about.
+///
+/// Serializes with its name under `event` and durations in whole seconds, for the event stream.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// Sent when the timer of a stage is first started
+    StageStarted {
+        stage: Stage,
+        #[serde(rename = "duration_secs", serialize_with = "as_secs")]
+        duration: Duration,
+    },
+    StageFinished {
+        stage: Stage,
+        /// Whether the stage ran its full duration rather than being cut short
+        completed: bool,
+        next_stage: Stage,
+        #[serde(rename = "next_duration_secs", serialize_with = "as_secs")]
+        next_duration: Duration,
+        /// Whether the finished stage completed today's goal of pomodoros
+        daily_goal_reached: bool,
+    },
+    /// Sent once per cycle, when the stage is about to end
+    StageEnding {
+        stage: Stage,
+        #[serde(rename = "remaining_secs", serialize_with = "as_secs")]
+        remaining: Duration,
+    },
+    PauseStarted {
+        stage: Stage,
+        #[serde(rename = "remaining_secs", serialize_with = "as_secs")]
+        remaining: Duration,
+    },
+    Resumed {
+        stage: Stage,
+        #[serde(rename = "remaining_secs", serialize_with = "as_secs")]
+        remaining: Duration,
+        #[serde(rename = "paused_for_secs", serialize_with = "as_secs")]
+        paused_for: Duration,
+    },
+    /// Sent once an annotated interruption is over, with its final annotation
+    InterruptionAnnotated {
+        stage: Stage,
+        annotation: String,
+    },
+    LongPause {
+        #[serde(rename = "paused_for_secs", serialize_with = "as_secs")]
+        paused_for: Duration,
+    },
+    /// Sent whenever the timer of a stage starts or stops running
+    TimerRunning {
+        stage: Stage,
+        running: bool,
+    },
+    /// Sent on every edit of the annotation of the ongoing interruption
+    AnnotationUpdated {
+        annotation: Option<String>,
+    },
+    ViewChanged {
+        view: AppView,
+    },
+}
+
+/// Stdout, as long as it is a terminal; escape sequences written anywhere else,
+/// e.g. to a daemon's event stream, would only be garbage.
+fn terminal_stdout() -> io::Result<io::Stdout> {
+    let stdout = io::stdout();
+
+    match stdout.is_terminal() {
+        true => Ok(stdout),
+        false => Err(io::Error::new(
+            ErrorKind::NotFound,
+            "stdout isn't a terminal",
+        )),
+    }
+}
+
+fn as_secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
+
+/// Lets the user know about an event, one way or another.
+///
+/// Events a notifier has nothing to do with are simply ignored; an error means that
+/// the notifier couldn't deliver an event it should have, e.g. without an audio device.
+pub trait Notifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()>;
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifierKind {
+    Sound,
+    Bell,
+    Desktop,
+    Terminal,
+}
+
+/// Sends every event to each group of notifiers.
+/// Within a group, the event goes to the first notifier which manages to deliver it.
+#[derive(Default)]
+pub struct Notifiers {
+    groups: Vec<Vec<Box<dyn Notifier>>>,
+}
+
+impl Notifiers {
+    pub fn new(groups: Vec<Vec<Box<dyn Notifier>>>) -> Notifiers {
+        Notifiers { groups }
+    }
+
+    /// Builds the groups of notifiers listed in `config`.
+    pub fn from_config(config: &Config) -> Notifiers {
+        let groups = config
+            .notifiers
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|kind| -> Box<dyn Notifier> {
+                        match kind {
+                            NotifierKind::Sound => Box::new(SoundNotifier::new(
+                                config.sounds.clone(),
+                                config.ticking.clone(),
+                            )),
+                            NotifierKind::Bell => Box::new(BellNotifier),
+                            NotifierKind::Desktop => Box::new(DesktopNotifier::default()),
+                            NotifierKind::Terminal => Box::new(TerminalNotifier),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Hooks are the user's own commands, so they run regardless of the other notifiers
+        let mut notifiers = Notifiers::new(groups);
+        if config.hooks.is_any_set() {
+            notifiers.add(Box::new(HookNotifier::new(config.hooks.clone())));
+        }
+        if !config.webhooks.is_empty() {
+            notifiers.add(Box::new(WebhookNotifier::new(config.webhooks.clone())));
+        }
+
+        notifiers
+    }
+
+    /// Sends every event to `notifier` too, regardless of the other groups.
+    pub fn add(&mut self, notifier: Box<dyn Notifier>) {
+        self.groups.push(vec![notifier]);
+    }
+
+    pub fn notify(&mut self, event: &Event) {
+        for group in self.groups.iter_mut() {
+            for notifier in group.iter_mut() {
+                if notifier.notify(event).is_ok() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Title and body of the message announcing `event`, if it is worth one.
+fn message(event: &Event) -> Option<(String, String)> {
+    match event {
+        Event::StageFinished {
+            stage,
+            next_stage,
+            next_duration,
+            daily_goal_reached,
+            ..
+        } => {
+            let summary = match daily_goal_reached {
+                true => format!("{} is over, daily goal reached!", stage),
+                false => format!("{} is over", stage),
+            };
+            let body = format!(
+                "Next up: {} ({})",
+                next_stage,
+                format_minutes(next_duration)
+            );
+            Some((summary, body))
+        }
+        Event::StageEnding { stage, remaining } => Some((
+            format!("{} ends in {}", stage, format_minutes(remaining)),
+            String::from("Time to wrap up"),
+        )),
+        Event::LongPause { paused_for } => Some((
+            String::from("Still paused"),
+            format!(
+                "The timer has been paused for {} min",
+                paused_for.as_secs() / 60
+            ),
+        )),
+        Event::StageStarted { .. }
+        | Event::PauseStarted { .. }
+        | Event::Resumed { .. }
+        | Event::InterruptionAnnotated { .. }
+        | Event::TimerRunning { .. }
+        | Event::AnnotationUpdated { .. }
+        | Event::ViewChanged { .. } => None,
+    }
+}
+
+fn format_minutes(duration: &Duration) -> String {
+    match duration.as_secs() {
+        secs if secs < 60 => format!("{} s", secs),
+        secs => format!("{} min", secs / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> Event {
+        Event::LongPause {
+            paused_for: Duration::from_secs(600),
+        }
+    }
+
+    #[test]
+    fn each_group_gets_the_event_from_its_first_working_notifier() {
+        let failing = RecordingNotifier::failing();
+        let fallback = RecordingNotifier::default();
+        let unused_fallback = RecordingNotifier::default();
+        let other_group = RecordingNotifier::default();
+
+        let mut notifiers = Notifiers::new(vec![
+            vec![
+                Box::new(failing.clone()),
+                Box::new(fallback.clone()),
+                Box::new(unused_fallback.clone()),
+            ],
+            vec![Box::new(other_group.clone())],
+        ]);
+        notifiers.notify(&event());
+
+        assert_eq!(failing.events(), vec![event()]);
+        assert_eq!(fallback.events(), vec![event()]);
+        assert!(unused_fallback.events().is_empty());
+        assert_eq!(other_group.events(), vec![event()]);
+    }
+
+    #[test]
+    fn a_group_whose_notifiers_all_fail_does_not_hold_up_the_others() {
+        let failing = RecordingNotifier::failing();
+        let added = RecordingNotifier::default();
+
+        let mut notifiers = Notifiers::new(vec![vec![Box::new(failing.clone())]]);
+        notifiers.add(Box::new(added.clone()));
+        notifiers.notify(&event());
+        notifiers.notify(&event());
+
+        assert_eq!(failing.events(), vec![event(), event()]);
+        assert_eq!(added.events(), vec![event(), event()]);
+    }
+}
@@ -0,0 +1,37 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use super::{Event, Notifier};
+
+/// Keeps the events it receives instead of delivering them.
+/// Clones share the same events, so one clone can be handed to the app
+/// and the other one inspected afterwards.
+#[derive(Clone, Default)]
+pub struct RecordingNotifier {
+    events: Rc<RefCell<Vec<Event>>>,
+    /// Makes every delivery fail, to exercise the fallbacks
+    failing: bool,
+}
+
+impl RecordingNotifier {
+    pub fn failing() -> RecordingNotifier {
+        RecordingNotifier {
+            failing: true,
+            ..RecordingNotifier::default()
+        }
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+}
+
+impl Notifier for RecordingNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        self.events.borrow_mut().push(event.clone());
+
+        match self.failing {
+            true => Err(io::Error::other("failing on purpose")),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Cursor, ErrorKind, Read, Seek},
+    path::Path,
+    time::Duration,
+};
+
+use rodio::{
+    source::{SineWave, Zero},
+    Decoder, Device, DeviceTrait, Sample, Sink, Source,
+};
+
+use super::{melody::melody, Event, Notifier};
+use crate::config::{SoundConfig, SoundsConfig, TickingConfig};
+use crate::pomodoro_technique::Stage;
+
+/// Played for every event without a sound file of its own.
+const DEFAULT_SOUND: &[u8] = include_bytes!("../../sounds/beep.wav");
+/// How much of the default sound is played when no duration is set, as it runs long.
+const DEFAULT_SOUND_DURATION: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug)]
+enum SoundEvent {
+    WorkEnd,
+    BreakEnd,
+    PauseStarted,
+    LongPauseReminder,
+    DailyGoalReached,
+    StageEnding,
+}
+
+const SOUND_EVENTS: [SoundEvent; 6] = [
+    SoundEvent::WorkEnd,
+    SoundEvent::BreakEnd,
+    SoundEvent::PauseStarted,
+    SoundEvent::LongPauseReminder,
+    SoundEvent::DailyGoalReached,
+    SoundEvent::StageEnding,
+];
+
+/// Plays the sound configured for each event with rodio,
+/// and the ticking sound while a work stage is running.
+pub struct SoundNotifier {
+    sounds: SoundsConfig,
+    ticking: TickingConfig,
+    /// Created on the first run of the ticking sound, then paused and resumed
+    ticking_sink: Option<Sink>,
+    /// Cleared under WSL, where rodio can't reach the audio output
+    available: bool,
+}
+
+impl SoundNotifier {
+    /// Checks that every sound file set in `sounds` can be decoded,
+    /// so that a typo in the config shows up now rather than as silence later.
+    /// Events whose file can't be loaded get the default sound, with a warning.
+    pub fn new(mut sounds: SoundsConfig, ticking: TickingConfig) -> SoundNotifier {
+        for event in SOUND_EVENTS.iter() {
+            let sound = sound_mut(&mut sounds, *event);
+            if let (true, Some(file)) = (sound.enabled, sound.file.as_ref()) {
+                if let Err(err) = decode_file(file) {
+                    warn_unloadable(*event, file, &err);
+                    sound.file = None;
+                }
+            }
+        }
+
+        SoundNotifier {
+            sounds,
+            ticking,
+            ticking_sink: None,
+            available: !wsl::is_wsl(),
+        }
+    }
+
+    fn play(&mut self, event: SoundEvent) -> io::Result<()> {
+        let sound = sound_mut(&mut self.sounds, event).clone();
+        if !sound.enabled {
+            return Ok(());
+        }
+
+        // A file which went missing since startup still gets a sound,
+        // the default one from now on, as at startup
+        let source = match sound.file.as_deref() {
+            Some(file) => match decode_file(file) {
+                Ok(source) => Some(source),
+                Err(err) => {
+                    warn_unloadable(event, file, &err);
+                    sound_mut(&mut self.sounds, event).file = None;
+                    None
+                }
+            },
+            None => None,
+        };
+
+        self.with_device(|device| match source {
+            Some(source) => play_source(device, source, sound.volume, sound.duration),
+            _ if !sound.melody.is_empty() => {
+                play_source(device, melody(&sound.melody), sound.volume, sound.duration)
+            }
+            _ => {
+                let source = decode(Cursor::new(DEFAULT_SOUND)).expect("embedded sound is valid");
+                let duration = sound.duration.or(Some(DEFAULT_SOUND_DURATION));
+                play_source(device, source, sound.volume, duration)
+            }
+        })
+    }
+
+    fn set_ticking(&mut self, running: bool) -> io::Result<()> {
+        if !self.ticking.enabled {
+            return Ok(());
+        }
+
+        match (running, self.ticking_sink.as_ref()) {
+            (true, Some(sink)) => sink.play(),
+            (false, Some(sink)) => sink.pause(),
+            (true, None) => {
+                let volume = self.ticking.volume;
+                let sink = self.with_device(|device| {
+                    let sink = Sink::new(device);
+                    sink.set_volume(volume);
+                    sink.append(tick());
+                    sink
+                })?;
+                self.ticking_sink = Some(sink);
+            }
+            (false, None) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Runs `play` with the default output device.
+    fn with_device<T, F: FnOnce(&Device) -> T>(&self, play: F) -> io::Result<T> {
+        if !self.available {
+            return Err(io::Error::other("no audio output"));
+        }
+
+        output_device().map(|device| play(&device))
+    }
+}
+
+impl Notifier for SoundNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        match event {
+            Event::StageFinished {
+                stage,
+                daily_goal_reached,
+                ..
+            } => self.play(match stage {
+                Stage::Work if *daily_goal_reached => SoundEvent::DailyGoalReached,
+                Stage::Work => SoundEvent::WorkEnd,
+                Stage::ShortBreak | Stage::LongBreak => SoundEvent::BreakEnd,
+            }),
+            Event::StageEnding { .. } => self.play(SoundEvent::StageEnding),
+            Event::PauseStarted { .. } => self.play(SoundEvent::PauseStarted),
+            Event::LongPause { .. } => self.play(SoundEvent::LongPauseReminder),
+            Event::TimerRunning { stage, running } => {
+                self.set_ticking(*running && *stage == Stage::Work)
+            }
+            Event::StageStarted { .. }
+            | Event::Resumed { .. }
+            | Event::InterruptionAnnotated { .. }
+            | Event::AnnotationUpdated { .. }
+            | Event::ViewChanged { .. } => Ok(()),
+        }
+    }
+}
+
+/// A short click followed by silence, a second long in total, forever.
+fn tick() -> impl Source<Item = f32> + Send {
+    let click = SineWave::new(1800).take_duration(Duration::from_millis(15));
+    let silence = Zero::<f32>::new(1, 48000).take_duration(Duration::from_secs(1));
+
+    click.mix(silence).buffered().repeat_infinite()
+}
+
+fn sound_mut(sounds: &mut SoundsConfig, event: SoundEvent) -> &mut SoundConfig {
+    match event {
+        SoundEvent::WorkEnd => &mut sounds.work_end,
+        SoundEvent::BreakEnd => &mut sounds.break_end,
+        SoundEvent::PauseStarted => &mut sounds.pause_started,
+        SoundEvent::LongPauseReminder => &mut sounds.long_pause_reminder,
+        SoundEvent::DailyGoalReached => &mut sounds.daily_goal_reached,
+        SoundEvent::StageEnding => &mut sounds.stage_ending,
+    }
+}
+
+fn warn_unloadable(event: SoundEvent, file: &Path, err: &io::Error) {
+    eprintln!(
+        "warning: can't load the {:?} sound {}: {}; using the default sound",
+        event,
+        file.display(),
+        err
+    );
+}
+
+/// Plays `source` at `volume`, cut after `duration` seconds if set.
+fn play_source<S>(device: &Device, source: S, volume: f32, duration: Option<f32>)
+where
+    S: Source + Send + 'static,
+    S::Item: Sample + Send,
+{
+    let source = source.amplify(volume);
+
+    match duration {
+        Some(secs) => rodio::play_raw(
+            device,
+            source
+                .take_duration(Duration::from_secs_f32(secs))
+                .convert_samples(),
+        ),
+        None => rodio::play_raw(device, source.convert_samples()),
+    }
+}
+
+/// The default output device, as long as rodio can open it: rodio panics
+/// on devices without a name or an output format, so those are turned down.
+fn output_device() -> io::Result<Device> {
+    let device = rodio::default_output_device()
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no audio device"))?;
+
+    match (device.name(), device.default_output_format()) {
+        (Ok(_), Ok(_)) => Ok(device),
+        (Err(err), _) => Err(io::Error::other(err.to_string())),
+        (_, Err(err)) => Err(io::Error::other(err.to_string())),
+    }
+}
+
+fn decode_file(path: &Path) -> io::Result<Decoder<BufReader<File>>> {
+    decode(BufReader::new(File::open(path)?))
+}
+
+fn decode<R: Read + Seek + Send + 'static>(reader: R) -> io::Result<Decoder<R>> {
+    Decoder::new(reader).map_err(|err| io::Error::new(ErrorKind::InvalidData, err.to_string()))
+}
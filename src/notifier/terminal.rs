@@ -0,0 +1,54 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+use super::{message, terminal_stdout, Event, Notifier};
+
+/// Asks the terminal emulator for a desktop notification with an OSC 9/777
+/// escape sequence, for when there is no notification server to talk to.
+#[derive(Default)]
+pub struct TerminalNotifier;
+
+impl Notifier for TerminalNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        match message(event) {
+            Some((summary, body)) => notify_over_terminal(&summary, &body),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes a notification escape sequence to the terminal: OSC 777 for the
+/// terminals known to understand it, OSC 9 for everything else.
+/// Inside tmux, the sequence is wrapped so that it reaches the outer terminal.
+fn notify_over_terminal(summary: &str, body: &str) -> io::Result<()> {
+    let summary = strip_control_chars(summary);
+    let body = strip_control_chars(body);
+
+    let sequence = match supports_osc_777() {
+        true => format!("\x1b]777;notify;{};{}\x07", summary, body),
+        false => format!("\x1b]9;{}: {}\x07", summary, body),
+    };
+
+    let sequence = match env::var_os("TMUX") {
+        Some(_) => format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b")),
+        None => sequence,
+    };
+
+    let mut stdout = terminal_stdout()?;
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
+
+fn supports_osc_777() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    term.contains("rxvt") || term.contains("foot") || env::var_os("VTE_VERSION").is_some()
+}
+
+/// Keeps user-provided text from terminating the escape sequence early.
+fn strip_control_chars(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() && *c != ';')
+        .collect()
+}
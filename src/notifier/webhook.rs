@@ -0,0 +1,346 @@
+use std::{
+    io,
+    sync::mpsc::{self, Sender},
+    thread,
+    time::Duration,
+};
+
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::{Event, Notifier};
+use crate::config::WebhookConfig;
+use crate::pomodoro_technique::Stage;
+
+/// Every name webhooks can ask for events by, see `names`.
+pub const WEBHOOK_EVENTS: [&str; 13] = [
+    "stage_started",
+    "stage_finished",
+    "pomodoro_completed",
+    "pomodoro_voided",
+    "daily_goal_reached",
+    "stage_ending",
+    "pause_started",
+    "resumed",
+    "interruption_annotated",
+    "long_pause",
+    "timer_running",
+    "annotation_updated",
+    "view_changed",
+];
+
+/// How long to wait before the first retry; every retry after that waits twice as long.
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+
+/// POSTs the events each webhook is interested in, as JSON.
+///
+/// Deliveries are queued for a thread of their own, so a slow or unreachable server
+/// never holds up the timer, and events reach each server in the order they happened;
+/// those which still fail after every retry are dropped.
+pub struct WebhookNotifier {
+    webhooks: Vec<WebhookConfig>,
+    deliveries: Sender<(WebhookConfig, String)>,
+}
+
+impl WebhookNotifier {
+    pub fn new(webhooks: Vec<WebhookConfig>) -> WebhookNotifier {
+        let (deliveries, pending_deliveries) = mpsc::channel::<(WebhookConfig, String)>();
+
+        thread::spawn(move || {
+            for (webhook, body) in pending_deliveries {
+                deliver(&webhook, &body, FIRST_BACKOFF);
+            }
+        });
+
+        WebhookNotifier {
+            webhooks,
+            deliveries,
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&mut self, event: &Event) -> io::Result<()> {
+        let names = names(event);
+
+        let mut fields = serde_json::to_value(event)?;
+        if let Value::Object(fields) = &mut fields {
+            fields.insert(String::from("time"), Value::from(Local::now().to_rfc3339()));
+        }
+
+        for webhook in self.webhooks.iter() {
+            if !webhook
+                .events
+                .iter()
+                .any(|name| names.contains(&name.as_str()))
+            {
+                continue;
+            }
+
+            let body = match webhook.template.as_ref() {
+                Some(template) => fill_template(template, &fields),
+                None => fields.to_string(),
+            };
+
+            self.deliveries
+                .send((webhook.clone(), body))
+                .map_err(|_| io::Error::other("the webhook thread has exited"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The names webhooks can ask for `event` by: its own, as in the event stream,
+/// and, for the end of a pomodoro, how it went.
+fn names(event: &Event) -> Vec<&'static str> {
+    match event {
+        Event::StageFinished {
+            stage: Stage::Work,
+            completed,
+            daily_goal_reached,
+            ..
+        } => {
+            let mut names = vec!["stage_finished"];
+            names.push(match completed {
+                true => "pomodoro_completed",
+                false => "pomodoro_voided",
+            });
+            if *daily_goal_reached {
+                names.push("daily_goal_reached");
+            }
+            names
+        }
+        Event::StageFinished { .. } => vec!["stage_finished"],
+        Event::StageStarted { .. } => vec!["stage_started"],
+        Event::StageEnding { .. } => vec!["stage_ending"],
+        Event::PauseStarted { .. } => vec!["pause_started"],
+        Event::Resumed { .. } => vec!["resumed"],
+        Event::InterruptionAnnotated { .. } => vec!["interruption_annotated"],
+        Event::LongPause { .. } => vec!["long_pause"],
+        Event::TimerRunning { .. } => vec!["timer_running"],
+        Event::AnnotationUpdated { .. } => vec!["annotation_updated"],
+        Event::ViewChanged { .. } => vec!["view_changed"],
+    }
+}
+
+/// Replaces `{field}` with that field of the event, escaped so that it can sit
+/// inside a JSON string, and `{event_json}` with the whole event.
+///
+/// The template is filled in a single pass, so that braces in the values
+/// themselves, such as in an annotation, are left as they are.
+fn fill_template(template: &str, fields: &Value) -> String {
+    let mut body = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        body.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let placeholder = rest[1..].find('}').and_then(|end| {
+            let name = &rest[1..=end];
+            placeholder_value(name, fields).map(|value| (name.len(), value))
+        });
+        match placeholder {
+            Some((length, value)) => {
+                body.push_str(&value);
+                rest = &rest[length + 2..];
+            }
+            None => {
+                body.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    body.push_str(rest);
+
+    body
+}
+
+/// What the placeholder `name` stands for, if anything.
+fn placeholder_value(name: &str, fields: &Value) -> Option<String> {
+    let value = match (name, fields.get(name)) {
+        ("event_json", _) => fields.to_string(),
+        (_, Some(Value::String(value))) => {
+            let quoted = Value::from(value.as_str()).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        }
+        (_, Some(value)) => value.to_string(),
+        (_, None) => return None,
+    };
+
+    Some(value)
+}
+
+/// Sends `body` to `webhook`, retrying with an exponential backoff from `first_backoff`
+/// when the server can't be reached or fails on its side.
+fn deliver(webhook: &WebhookConfig, body: &str, first_backoff: Duration) {
+    let agent = ureq::AgentBuilder::new().timeout(webhook.timeout).build();
+    let signature = webhook.secret.as_ref().map(|secret| sign(secret, body));
+    let mut backoff = first_backoff;
+
+    for attempt in 0..=webhook.retries {
+        if attempt > 0 {
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+
+        let mut request = agent
+            .post(&webhook.url)
+            .set("Content-Type", "application/json");
+        if let Some(signature) = signature.as_ref() {
+            request = request.set("X-Pomodoro-Signature", signature);
+        }
+
+        match request.send_string(body) {
+            Ok(_) => return,
+            // The server understood, and turned the event down, which a retry won't change
+            Err(ureq::Error::Status(code, _)) if code < 500 && code != 429 => return,
+            Err(_) => {}
+        }
+    }
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body`, keyed with `secret`.
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(body.as_bytes());
+
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!("sha256={}", digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tiny_http::{Response, Server};
+
+    use super::*;
+
+    /// The body and signature of each request received.
+    type Received = thread::JoinHandle<Vec<(String, Option<String>)>>;
+
+    /// A server answering every request with the next of `codes`,
+    /// returning what it received once done.
+    fn serve(codes: Vec<u16>) -> (String, Received) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+
+        let received = thread::spawn(move || {
+            let mut received = Vec::new();
+            for code in codes {
+                let mut request = server.recv().unwrap();
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let signature = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("X-Pomodoro-Signature"))
+                    .map(|header| header.value.to_string());
+                received.push((body, signature));
+
+                request.respond(Response::empty(code)).unwrap_or_default();
+            }
+            received
+        });
+
+        (url, received)
+    }
+
+    fn webhook(url: String, retries: u32) -> WebhookConfig {
+        WebhookConfig {
+            url,
+            events: vec![String::from("stage_finished")],
+            timeout: Duration::from_secs(5),
+            retries,
+            secret: Some(String::from("key")),
+            template: None,
+        }
+    }
+
+    #[test]
+    fn fills_templates_with_escaped_fields() {
+        let fields = json!({
+            "event": "interruption_annotated",
+            "annotation": "a \"quoted\"\nnote\\",
+            "interruptions": 2,
+        });
+
+        let body = fill_template(
+            r#"{"text": "{event}: {annotation}", "count": {interruptions}, "raw": {event_json}}"#,
+            &fields,
+        );
+
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["text"], "interruption_annotated: a \"quoted\"\nnote\\");
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["raw"], fields);
+    }
+
+    #[test]
+    fn leaves_placeholders_within_fields_alone() {
+        let fields = json!({
+            "event": "interruption_annotated",
+            "stage": "work",
+            "annotation": "left {stage} for {remaining}",
+        });
+
+        let body = fill_template(
+            r#"{"text": "{annotation} ({stage})", "raw": {event_json}}"#,
+            &fields,
+        );
+
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["text"], "left {stage} for {remaining} (work)");
+        assert_eq!(body["raw"], fields);
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn retries_when_the_server_fails() {
+        let (url, received) = serve(vec![500, 503, 200]);
+
+        deliver(&webhook(url, 3), "{}", Duration::from_millis(1));
+
+        let received = received.join().unwrap();
+        assert_eq!(received.len(), 3);
+        for (body, signature) in received {
+            assert_eq!(body, "{}");
+            assert_eq!(signature, Some(sign("key", "{}")));
+        }
+    }
+
+    #[test]
+    fn gives_up_when_the_server_turns_the_event_down() {
+        let (url, received) = serve(vec![400, 200]);
+        let webhook = webhook(url.clone(), 3);
+
+        deliver(&webhook, "{}", Duration::from_millis(1));
+        // Had it retried, this would be the server's second request
+        deliver(&webhook, "second", Duration::from_millis(1));
+
+        let bodies: Vec<_> = received
+            .join()
+            .unwrap()
+            .into_iter()
+            .map(|(body, _)| body)
+            .collect();
+        assert_eq!(bodies, vec!["{}", "second"]);
+    }
+}
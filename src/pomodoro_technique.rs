@@ -1,48 +1,140 @@
-use std::time::Instant;
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::git::Commit;
 
 /// A pomodoro cycle.
+/// Its instants serialize as wall-clock times, so that it can be shared with other processes.
+#[derive(Serialize, Deserialize)]
 pub struct Cycle {
     pub stage_iteration: usize,
+    pub stage: Stage,
+    pub task: Option<String>,
+    /// Whether the cycle ran its full duration rather than being skipped
+    pub completed: bool,
+    #[serde(with = "optional_instant_as_wall_clock")]
     pub started_at: Option<Instant>,
+    #[serde(with = "optional_instant_as_wall_clock")]
     pub finished_at: Option<Instant>,
     pub interruption_history: Vec<Interruption>,
     pub interruption: Option<Interruption>,
+    /// Commits made during the cycle, recorded once it finishes
+    #[serde(default)]
+    pub commits: Vec<Commit>,
 }
 
 impl Clone for Cycle {
     fn clone(&self) -> Cycle {
         Cycle {
             stage_iteration: self.stage_iteration,
+            stage: self.stage,
+            task: self.task.clone(),
+            completed: self.completed,
             started_at: self.started_at.clone(),
             finished_at: self.finished_at.clone(),
             interruption_history: self.interruption_history.clone(),
             interruption: self.interruption.clone(),
+            commits: self.commits.clone(),
         }
     }
 }
 
 impl Cycle {
-    pub fn new(stage_iteration: usize) -> Cycle {
+    pub fn new(stage_iteration: usize, stage: Stage) -> Cycle {
         Cycle {
             stage_iteration,
+            stage,
+            task: None,
+            completed: false,
             started_at: None,
             finished_at: None,
             interruption_history: Vec::new(),
             interruption: None,
+            commits: Vec::new(),
+        }
+    }
+
+    /// Total time spent on finished interruptions.
+    pub fn paused_time(&self) -> Duration {
+        self.interruption_history
+            .iter()
+            .fold(Duration::new(0, 0), |total, interruption| {
+                total + interruption.duration()
+            })
+    }
+
+    /// Time actually spent on the stage, discounting interruptions.
+    /// Only meaningful once the cycle is finished.
+    pub fn focused_time(&self) -> Duration {
+        match (self.started_at, self.finished_at) {
+            (Some(started_at), Some(finished_at)) => {
+                (finished_at - started_at).saturating_sub(self.paused_time())
+            }
+            _ => Duration::new(0, 0),
         }
     }
 }
 
 /// Pomodoro stages.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Stage {
     Work,
     ShortBreak,
     LongBreak,
 }
 
+impl Stage {
+    /// The name of the stage in machine-readable output, as it is serialized.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Work => "work",
+            Stage::ShortBreak => "short_break",
+            Stage::LongBreak => "long_break",
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    /// Parses a stage name as it is serialized, or its initial, e.g. `short_break` or `s`.
+    fn from_str(name: &str) -> Result<Stage, String> {
+        match name.trim() {
+            "work" | "w" => Ok(Stage::Work),
+            "short_break" | "s" => Ok(Stage::ShortBreak),
+            "long_break" | "l" => Ok(Stage::LongBreak),
+            name => Err(format!(
+                "invalid stage `{}`, expected work, short_break or long_break",
+                name
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Stage::Work => "Work",
+            Stage::ShortBreak => "Short break",
+            Stage::LongBreak => "Long break",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// An interruption to a pomodoro stage.
+#[derive(Serialize, Deserialize)]
 pub struct Interruption {
+    #[serde(with = "instant_as_wall_clock")]
     pub started_at: Instant,
+    #[serde(with = "optional_instant_as_wall_clock")]
     pub finished_at: Option<Instant>,
     pub annotation: Option<String>,
 }
@@ -65,4 +157,68 @@ impl Interruption {
             annotation: None,
         }
     }
+
+    /// How long the interruption lasted; zero while it is ongoing.
+    pub fn duration(&self) -> Duration {
+        match self.finished_at {
+            Some(finished_at) => finished_at - self.started_at,
+            None => Duration::new(0, 0),
+        }
+    }
+}
+
+/// Converts a monotonic instant into the wall-clock time it happened at.
+pub fn wall_clock(instant: Instant) -> DateTime<Local> {
+    let now = Instant::now();
+
+    if instant <= now {
+        Local::now()
+            - chrono::Duration::from_std(now - instant).unwrap_or_else(|_| chrono::Duration::zero())
+    } else {
+        Local::now()
+            + chrono::Duration::from_std(instant - now).unwrap_or_else(|_| chrono::Duration::zero())
+    }
+}
+
+/// Converts a wall-clock time into the monotonic instant it happened at.
+pub fn instant_at(time: DateTime<Local>) -> Instant {
+    let now = Instant::now();
+    let offset = time - Local::now();
+
+    match offset.to_std() {
+        Ok(ahead) => now + ahead,
+        Err(_) => {
+            let behind = (-offset).to_std().unwrap_or_default();
+            now.checked_sub(behind).unwrap_or(now)
+        }
+    }
+}
+
+mod instant_as_wall_clock {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        wall_clock(*instant).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        DateTime::<Local>::deserialize(deserializer).map(instant_at)
+    }
+}
+
+mod optional_instant_as_wall_clock {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        instant: &Option<Instant>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        instant.map(wall_clock).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Instant>, D::Error> {
+        Option::<DateTime<Local>>::deserialize(deserializer).map(|time| time.map(instant_at))
+    }
 }
@@ -0,0 +1,128 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    time::Duration,
+};
+
+use chrono::Local;
+use crossterm::{
+    queue,
+    style::{self, ResetColor, SetForegroundColor},
+    terminal, Result,
+};
+use tui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+use crate::history::{pomodoros_by_day, streaks, CycleRecord};
+use crate::widgets::{format_duration, CalendarHeatmap, CALENDAR_HEATMAP_HEIGHT};
+
+/// Prints a summary of the persisted history to stdout,
+/// optionally followed by a calendar heatmap of the last year.
+pub fn print(records: &[CycleRecord], heatmap: bool) -> Result<()> {
+    let mut out = io::stdout();
+    let today = Local::now().date_naive();
+
+    let pomodoros: Vec<&CycleRecord> = records.iter().filter(|r| r.is_pomodoro()).collect();
+    let focused = pomodoros.iter().fold(Duration::new(0, 0), |total, record| {
+        total + Duration::from_secs(record.focused_secs)
+    });
+    let today_count = pomodoros
+        .iter()
+        .filter(|record| record.started_at.date_naive() == today)
+        .count();
+    let (current, longest) = streaks(&pomodoros_by_day(records), today);
+
+    writeln!(
+        out,
+        "Pomodoros:      {} ({} today)",
+        pomodoros.len(),
+        today_count
+    )?;
+    writeln!(out, "Focused time:   {}", format_duration(focused))?;
+
+    // Only shown to those who record their commits
+    let commits: usize = pomodoros.iter().map(|record| record.commits.len()).sum();
+    if commits > 0 {
+        writeln!(
+            out,
+            "Commits:        {} ({:.1} per pomodoro)",
+            commits,
+            commits as f64 / pomodoros.len() as f64
+        )?;
+    }
+    writeln!(out, "Current streak: {} days", current)?;
+    writeln!(out, "Longest streak: {} days", longest)?;
+
+    if heatmap {
+        writeln!(out)?;
+        print_heatmap(&mut out, records, today)?;
+    }
+
+    Ok(())
+}
+
+fn print_heatmap<W: Write>(
+    out: &mut W,
+    records: &[CycleRecord],
+    today: chrono::NaiveDate,
+) -> Result<()> {
+    let colored = io::stdout().is_terminal();
+    let width = match terminal::size() {
+        Ok((columns, _)) if colored => columns.min(CalendarHeatmap::full_width()),
+        _ => CalendarHeatmap::full_width(),
+    };
+
+    let area = Rect::new(0, 0, width, CALENDAR_HEATMAP_HEIGHT);
+    let mut buf = Buffer::empty(area);
+
+    CalendarHeatmap::default()
+        .records(records)
+        .last_day(today)
+        .monochrome(!colored)
+        .render(area, &mut buf);
+
+    for y in 0..area.height {
+        let mut line = String::new();
+        for x in 0..area.width {
+            let cell = buf.get(x, y);
+            match to_crossterm_color(cell.fg) {
+                Some(color) if colored => {
+                    write!(out, "{}", line)?;
+                    line.clear();
+                    queue!(out, SetForegroundColor(color))?;
+                    write!(out, "{}", cell.symbol)?;
+                    queue!(out, ResetColor)?;
+                }
+                _ => line.push_str(&cell.symbol),
+            }
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn to_crossterm_color(color: Color) -> Option<style::Color> {
+    let color = match color {
+        Color::Reset => return None,
+        Color::Black => style::Color::Black,
+        Color::Red => style::Color::DarkRed,
+        Color::Green => style::Color::DarkGreen,
+        Color::Yellow => style::Color::DarkYellow,
+        Color::Blue => style::Color::DarkBlue,
+        Color::Magenta => style::Color::DarkMagenta,
+        Color::Cyan => style::Color::DarkCyan,
+        Color::Gray => style::Color::Grey,
+        Color::DarkGray => style::Color::DarkGrey,
+        Color::LightRed => style::Color::Red,
+        Color::LightGreen => style::Color::Green,
+        Color::LightBlue => style::Color::Blue,
+        Color::LightYellow => style::Color::Yellow,
+        Color::LightMagenta => style::Color::Magenta,
+        Color::LightCyan => style::Color::Cyan,
+        Color::White => style::Color::White,
+        Color::Rgb(r, g, b) => style::Color::Rgb { r, g, b },
+        Color::Indexed(idx) => style::Color::AnsiValue(idx),
+    };
+
+    Some(color)
+}
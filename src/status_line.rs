@@ -0,0 +1,223 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process, thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::control::{self, Client, ControlCommand, Response, Status};
+use crate::pomodoro_technique::Stage;
+
+pub const DEFAULT_STATUS_FORMAT: &str = "{stage_icon} {remaining}";
+
+/// How often `--watch` looks at the timer again.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What `pomodoro status` prints for each state of the timer.
+pub enum Output {
+    /// Every field, e.g. `Work 24:58 | paused 00:03 | interruptions 1`
+    Summary,
+    /// A line filled in with `format`
+    Format(String),
+    /// JSON for a waybar custom module, its text filled in with `format`
+    Waybar(String),
+}
+
+impl Output {
+    /// Renders `status`, or the timer not running at all when there is none.
+    pub fn render(&self, status: Option<&Status>) -> String {
+        match (self, status) {
+            (Output::Summary, Some(status)) => status.to_string(),
+            (Output::Format(format), Some(status)) => format_status(format, status),
+            (Output::Summary, None) | (Output::Format(_), None) => String::new(),
+            (Output::Waybar(format), Some(status)) => json!({
+                "text": format_status(format, status),
+                "tooltip": status.to_string(),
+                "alt": status.stage.as_str(),
+                "class": [status.stage.as_str(), state_name(status)],
+            })
+            .to_string(),
+            (Output::Waybar(_), None) => json!({
+                "text": "",
+                "tooltip": "Not running",
+                "alt": "stopped",
+                "class": ["stopped"],
+            })
+            .to_string(),
+        }
+    }
+}
+
+/// Fills in the placeholders of a status format:
+///
+/// - `{stage_icon}` (or `{icon}`): 🍅 while working, ☕ during breaks, ⏸ while paused
+/// - `{stage}`: the current stage
+/// - `{remaining}`: the time left in the current stage
+/// - `{paused}`: how long the timer has been paused, empty while it runs
+/// - `{interruptions}`: the interruptions of the current stage
+/// - `{task}`: the current task, if any
+/// - `{count}`: the pomodoros completed today
+pub fn format_status(format: &str, status: &Status) -> String {
+    let icon = match status.stage {
+        _ if status.paused => "⏸",
+        Stage::Work => "🍅",
+        Stage::ShortBreak | Stage::LongBreak => "☕",
+    };
+    let paused = match status.paused {
+        true => minutes_and_seconds(status.paused_secs),
+        false => String::new(),
+    };
+
+    let line = format
+        .replace("{stage_icon}", icon)
+        .replace("{icon}", icon)
+        .replace("{stage}", &status.stage.to_string())
+        .replace("{remaining}", &minutes_and_seconds(status.remaining_secs))
+        .replace("{paused}", &paused)
+        .replace("{interruptions}", &status.interruptions.to_string())
+        .replace("{task}", status.task.as_deref().unwrap_or_default())
+        .replace("{count}", &status.pomodoros_today.to_string());
+
+    // Control characters from the task would break the line, or an escape sequence
+    line.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Prints the state of the timer once, or on every change with `watch`
+/// until stdout is closed.
+pub fn print(output: &Output, watch: bool) -> io::Result<()> {
+    if !watch {
+        let status = current_status()?;
+        println!("{}", output.render(Some(&status)));
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    let mut last_line = None;
+
+    loop {
+        // The timer may be started or stopped while the bar is watching it
+        let line = output.render(current_status().ok().as_ref());
+        if last_line.as_ref() != Some(&line) {
+            writeln!(stdout, "{}", line)?;
+            stdout.flush()?;
+            last_line = Some(line);
+        }
+
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// Asks the running instance for its state, falling back to its state file
+/// when the control socket can't be reached.
+pub fn current_status() -> io::Result<Status> {
+    let error = match Client::connect().and_then(|mut client| client.send(ControlCommand::Status)) {
+        Ok(Response {
+            status: Some(status),
+            ..
+        }) => return Ok(status),
+        Ok(Response { error, .. }) => io::Error::other(error.unwrap_or_default()),
+        Err(err) => err,
+    };
+
+    control::state_path()
+        .and_then(|path| StateFile::read(&path))
+        .map_err(|_| error)
+}
+
+/// The state of the timer as last written by the instance owning it.
+#[derive(Serialize, Deserialize)]
+struct State {
+    pid: u32,
+    updated_at: DateTime<Local>,
+    status: Status,
+}
+
+/// Keeps the state file up to date while alive, and removes it once dropped.
+///
+/// The file is only written when the timer changes state, e.g. gets paused;
+/// readers work out the time remaining from when it was written.
+pub struct StateFile {
+    path: PathBuf,
+    last_status: Option<Status>,
+}
+
+impl StateFile {
+    pub fn new() -> io::Result<StateFile> {
+        Ok(StateFile {
+            path: control::state_path()?,
+            last_status: None,
+        })
+    }
+
+    pub fn update(&mut self, status: &Status) -> io::Result<()> {
+        if let Some(last_status) = self.last_status.as_ref() {
+            if !status.has_changed_state(last_status) {
+                return Ok(());
+            }
+        }
+
+        let state = State {
+            pid: process::id(),
+            updated_at: Local::now(),
+            status: status.clone(),
+        };
+
+        // Written aside then moved over, so readers never see half a file
+        let partial = self.path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec(&state)?)?;
+        fs::rename(&partial, &self.path)?;
+
+        self.last_status = Some(status.clone());
+        Ok(())
+    }
+
+    /// Reads the state file at `path`, with the times brought up to now.
+    fn read(path: &Path) -> io::Result<Status> {
+        let state: State = serde_json::from_slice(&fs::read(path)?)?;
+
+        // Left behind by an instance which got killed
+        let proc = Path::new("/proc");
+        if proc.is_dir() && !proc.join(state.pid.to_string()).exists() {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                "the instance which wrote the state file has exited",
+            ));
+        }
+
+        let elapsed = (Local::now() - state.updated_at)
+            .to_std()
+            .unwrap_or_default()
+            .as_secs();
+        let mut status = state.status;
+        match (status.started, status.paused) {
+            (true, false) => status.remaining_secs = status.remaining_secs.saturating_sub(elapsed),
+            (true, true) => status.paused_secs += elapsed,
+            (false, _) => {}
+        }
+
+        Ok(status)
+    }
+}
+
+impl Drop for StateFile {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).unwrap_or_default();
+    }
+}
+
+fn state_name(status: &Status) -> &'static str {
+    match (status.started, status.paused) {
+        (false, _) => "idle",
+        (true, true) => "paused",
+        (true, false) => "running",
+    }
+}
+
+fn minutes_and_seconds(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
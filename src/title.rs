@@ -0,0 +1,63 @@
+use std::io::Write;
+
+use crossterm::Result;
+
+use crate::app::App;
+use crate::control::Status;
+use crate::status_line::format_status;
+
+pub const DEFAULT_TITLE_FORMAT: &str = "{stage_icon} {stage} {remaining}";
+pub const DEFAULT_PAUSED_TITLE_FORMAT: &str = "{stage_icon} {paused}";
+
+/// Keeps the terminal title in sync with the app state, e.g. `🍅 Work 12:34`,
+/// or `⏸ 03:10` while paused.
+///
+/// The title is only written when it changes. The title which was there before
+/// is saved on the terminal's title stack on `enter` and restored on `leave`.
+/// Terminals without a title stack, like tmux, are left with an empty title
+/// instead, which they show as their default one.
+pub struct TerminalTitle {
+    format: String,
+    paused_format: String,
+    last_title: Option<String>,
+}
+
+impl TerminalTitle {
+    pub fn new(format: &str, paused_format: &str) -> TerminalTitle {
+        TerminalTitle {
+            format: String::from(format),
+            paused_format: String::from(paused_format),
+            last_title: None,
+        }
+    }
+
+    pub fn enter<W: Write>(&self, out: &mut W) -> Result<()> {
+        write!(out, "\x1b[22;0t")?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn leave<W: Write>(&self, out: &mut W) -> Result<()> {
+        write!(out, "\x1b]2;\x07\x1b[23;0t")?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn update<W: Write>(&mut self, out: &mut W, app: &App) -> Result<()> {
+        let status = Status::from_app(app);
+        let format = match status.paused {
+            true => &self.paused_format,
+            false => &self.format,
+        };
+
+        let title = format_status(format, &status);
+        if self.last_title.as_ref() == Some(&title) {
+            return Ok(());
+        }
+
+        write!(out, "\x1b]2;{}\x07", title)?;
+        out.flush()?;
+        self.last_title = Some(title);
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{App, AppView};
+use crate::widgets::{
+    AnnotationPopup, CalendarHeatmap, HistoryTable, InterruptionsPanel, PauseTimer,
+    ProductivityCharts, Timer, CALENDAR_HEATMAP_HEIGHT,
+};
+
+/// Screen areas that react to mouse clicks, as laid out by the last draw.
+#[derive(Default)]
+pub struct ClickableAreas {
+    timer: Rect,
+    popup: Option<Rect>,
+}
+
+impl ClickableAreas {
+    fn contains(area: Rect, column: u16, row: u16) -> bool {
+        column >= area.left() && column < area.right() && row >= area.top() && row < area.bottom()
+    }
+
+    pub fn is_on_timer(&self, column: u16, row: u16) -> bool {
+        Self::contains(self.timer, column, row)
+    }
+
+    pub fn is_outside_popup(&self, column: u16, row: u16) -> bool {
+        match self.popup {
+            Some(popup) => !Self::contains(popup, column, row),
+            None => false,
+        }
+    }
+}
+
+/// Renders the whole screen for the current state of `app`.
+pub fn draw<B: Backend>(f: &mut Frame<B>, app: &App, draw_borders: bool) -> ClickableAreas {
+    let mut clickable_areas = ClickableAreas::default();
+
+    let size = f.size();
+    f.render_widget(Clear, size);
+
+    if draw_borders {
+        f.render_widget(debug_block(), size);
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(
+            [
+                Constraint::Percentage(30),
+                Constraint::Percentage(30),
+                Constraint::Percentage(5),
+                Constraint::Percentage(15),
+                Constraint::Percentage(20),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    let task_area = centered(chunks[0], 40);
+    let pomodoro_timer_area = centered(chunks[1], 40);
+    let pause_timer_area = centered(chunks[2], 40);
+    let pause_annotation_area = centered(chunks[3], 40);
+
+    if draw_borders {
+        f.render_widget(debug_block(), pause_annotation_area);
+    }
+
+    if let Some(task) = app.get_task() {
+        let task_line = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(task_area)[1];
+
+        let paragraph = Paragraph::new(Span::from(task)).alignment(Alignment::Center);
+        f.render_widget(paragraph, task_line);
+    }
+
+    if let AppView::TaskPrompt = app.get_view() {
+        let popup = AnnotationPopup::default()
+            .title("TASK")
+            .input(app.get_task_input());
+        let (x, y) = popup.cursor(pause_annotation_area);
+
+        f.render_widget(popup, pause_annotation_area);
+        f.set_cursor(x, y);
+
+        clickable_areas.popup = Some(pause_annotation_area);
+    }
+
+    if app.is_paused() {
+        let (warn_after, alert_after) = app.get_long_pause_thresholds();
+        let pause_timer = PauseTimer::default()
+            .has_been_paused_for(app.get_pause_elapsed_time())
+            .thresholds(warn_after, alert_after)
+            .borders(draw_borders);
+
+        f.render_widget(pause_timer, pause_timer_area);
+
+        if let AppView::AnnotationPopup = app.get_view() {
+            let popup = AnnotationPopup::default().input(app.get_annotation_input());
+            let (x, y) = popup.cursor(pause_annotation_area);
+
+            f.render_widget(popup, pause_annotation_area);
+            f.set_cursor(x, y);
+
+            clickable_areas.popup = Some(pause_annotation_area);
+        }
+    }
+
+    let (is_due, remaining_time) = app.get_remaining_time();
+
+    let clock = Timer::default()
+        .time_remaining(&remaining_time)
+        .borders(draw_borders)
+        .is_paused(app.is_paused())
+        .ending(app.is_stage_ending())
+        .is_due(is_due);
+
+    f.render_widget(clock, pomodoro_timer_area);
+    clickable_areas.timer = pomodoro_timer_area;
+
+    if let AppView::History | AppView::InterruptionsList = app.get_view() {
+        let history_panel = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Percentage(10),
+                    Constraint::Percentage(80),
+                    Constraint::Percentage(10),
+                ]
+                .as_ref(),
+            )
+            .split(size);
+
+        let history_panel = centered(history_panel[1], 80);
+
+        if let AppView::History = app.get_view() {
+            let table = HistoryTable::default()
+                .cycles(app.get_todays_history())
+                .selected(app.get_selected_cycle())
+                .borders(draw_borders);
+
+            f.render_widget(table, history_panel);
+
+            clickable_areas.popup = Some(history_panel);
+        } else if app.is_inspecting_cycle() {
+            let table = HistoryTable::default()
+                .cycles(app.get_todays_history())
+                .selected(app.get_selected_cycle());
+
+            f.render_widget(table, history_panel);
+        }
+    }
+
+    if let AppView::Charts = app.get_view() {
+        let charts_panel = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Percentage(5),
+                    Constraint::Percentage(90),
+                    Constraint::Percentage(5),
+                ]
+                .as_ref(),
+            )
+            .split(size);
+
+        let charts_panel = centered(charts_panel[1], 90);
+
+        let charts = ProductivityCharts::default()
+            .records(app.get_records())
+            .last_day(app.get_charts_last_day())
+            .borders(draw_borders);
+
+        f.render_widget(charts, charts_panel);
+
+        clickable_areas.popup = Some(charts_panel);
+    }
+
+    if let AppView::Heatmap = app.get_view() {
+        let width = (CalendarHeatmap::full_width() + 2).min(size.width);
+        let height = (CALENDAR_HEATMAP_HEIGHT + 2).min(size.height);
+        let heatmap_panel = Rect::new(
+            size.x + (size.width - width) / 2,
+            size.y + (size.height - height) / 2,
+            width,
+            height,
+        );
+
+        let block = Block::default()
+            .title("YEAR")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let heatmap = CalendarHeatmap::default()
+            .records(app.get_records())
+            .borders(draw_borders);
+
+        f.render_widget(Clear, heatmap_panel);
+        f.render_widget(heatmap, block.inner(heatmap_panel));
+        f.render_widget(block, heatmap_panel);
+
+        clickable_areas.popup = Some(heatmap_panel);
+    }
+
+    if let AppView::InterruptionsList = app.get_view() {
+        let interruptions_panel = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(20),
+                ]
+                .as_ref(),
+            )
+            .split(size);
+
+        let interruptions_panel = centered(interruptions_panel[1], 20);
+
+        let panel = InterruptionsPanel::default()
+            .interruptions(app.get_interruption_history())
+            .selected(app.get_selected_interruption())
+            .borders(draw_borders);
+
+        f.render_widget(panel, interruptions_panel);
+
+        clickable_areas.popup = Some(interruptions_panel);
+    }
+
+    clickable_areas
+}
+
+/// Horizontally centers a column taking `percentage` of the width of `area`.
+fn centered(area: Rect, percentage: u16) -> Rect {
+    let margin = (100 - percentage) / 2;
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(margin),
+                Constraint::Percentage(percentage),
+                Constraint::Percentage(margin),
+            ]
+            .as_ref(),
+        )
+        .split(area)[1]
+}
+
+/// Block used to outline areas when debugging the layout.
+fn debug_block() -> Block<'static> {
+    Block::default()
+        .style(Style::default().fg(Color::Red))
+        .borders(Borders::ALL)
+}
@@ -0,0 +1,93 @@
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Widget},
+};
+
+use super::{TextField, TextInput};
+
+/// Input box for annotating the ongoing interruption.
+/// Given a title, it doubles as a prompt for any other line of text.
+#[derive(Clone, Default)]
+pub struct AnnotationPopup<'a> {
+    input: Option<&'a TextInput>,
+    title: &'a str,
+}
+
+impl<'a> AnnotationPopup<'a> {
+    pub fn input(mut self, input: &'a TextInput) -> AnnotationPopup<'a> {
+        self.input = Some(input);
+        self
+    }
+
+    pub fn title(mut self, title: &'a str) -> AnnotationPopup<'a> {
+        self.title = title;
+        self
+    }
+
+    /// Where the terminal cursor should be placed when the popup is rendered in `area`.
+    pub fn cursor(&self, area: Rect) -> (u16, u16) {
+        self.field().cursor(self.block().inner(area))
+    }
+
+    fn block(&self) -> Block<'a> {
+        Block::default()
+            .title(self.title)
+            .style(Style::default().fg(Color::Red))
+            .borders(Borders::ALL)
+    }
+
+    fn field(&self) -> TextField<'a> {
+        match self.input {
+            Some(input) => TextField::default().input(input),
+            None => TextField::default(),
+        }
+    }
+}
+
+impl<'a> Widget for AnnotationPopup<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = self.block();
+        let inner = block.inner(area);
+
+        block.render(area, buf);
+        self.field().render(inner, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::{lines, render};
+
+    #[test]
+    fn renders_the_input_in_a_titled_box() {
+        let mut input = TextInput::default();
+        input.set_value("phone");
+
+        let buffer = render(
+            AnnotationPopup::default().title("TASK").input(&input),
+            10,
+            3,
+        );
+
+        assert_eq!(
+            lines(&buffer),
+            vec!["┌TASK────┐", "│phone   │", "└────────┘"]
+        );
+        assert_eq!(buffer.get(0, 0).fg, Color::Red);
+    }
+
+    #[test]
+    fn scrolls_long_input_to_keep_the_cursor_in_sight() {
+        let mut input = TextInput::default();
+        input.set_value("a rather long call");
+
+        let popup = AnnotationPopup::default().input(&input);
+        let area = Rect::new(0, 0, 10, 3);
+
+        assert_eq!(popup.cursor(area), (8, 1));
+        assert_eq!(lines(&render(popup, 10, 3))[1], "│ng call │");
+    }
+}
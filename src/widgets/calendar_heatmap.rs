@@ -0,0 +1,294 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Widget},
+};
+
+use crate::history::{pomodoros_by_day, streaks, CycleRecord};
+
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "", "Wed", "", "Fri", "", "Sun"];
+const WEEKDAY_LABEL_WIDTH: u16 = 4;
+const CELL_WIDTH: u16 = 2;
+const CELL_SYMBOL: &str = "■";
+const MAX_WEEKS: u16 = 53;
+
+/// Symbols standing in for `LEVEL_COLORS` when colors aren't available.
+const LEVEL_SYMBOLS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+/// Colors from no pomodoros at all to a very busy day.
+const LEVEL_COLORS: [Color; 5] = [
+    Color::Indexed(237),
+    Color::Indexed(22),
+    Color::Indexed(28),
+    Color::Indexed(34),
+    Color::Indexed(40),
+];
+
+/// Rows taken by the heatmap: month labels, a row per weekday, the legend and the streaks.
+pub const CALENDAR_HEATMAP_HEIGHT: u16 = 10;
+
+/// GitHub-style calendar of completed pomodoros: a column per week,
+/// a row per weekday and a cell per day, colored by how many pomodoros it had.
+/// It shows as many weeks as fit in its area, up to a year.
+#[derive(Clone)]
+pub struct CalendarHeatmap<'a> {
+    records: &'a [CycleRecord],
+    last_day: NaiveDate,
+    monochrome: bool,
+    draw_borders: bool,
+}
+
+impl<'a> Default for CalendarHeatmap<'a> {
+    fn default() -> CalendarHeatmap<'a> {
+        CalendarHeatmap {
+            records: &[],
+            last_day: chrono::Local::now().date_naive(),
+            monochrome: false,
+            draw_borders: false,
+        }
+    }
+}
+
+impl<'a> CalendarHeatmap<'a> {
+    pub fn records(mut self, records: &'a [CycleRecord]) -> CalendarHeatmap<'a> {
+        self.records = records;
+        self
+    }
+
+    pub fn last_day(mut self, last_day: NaiveDate) -> CalendarHeatmap<'a> {
+        self.last_day = last_day;
+        self
+    }
+
+    /// Tells the levels apart by symbol rather than by color.
+    pub fn monochrome(mut self, monochrome: bool) -> CalendarHeatmap<'a> {
+        self.monochrome = monochrome;
+        self
+    }
+
+    pub fn borders(mut self, draw_borders: bool) -> CalendarHeatmap<'a> {
+        self.draw_borders = draw_borders;
+        self
+    }
+
+    /// Width needed to show a whole year.
+    pub fn full_width() -> u16 {
+        WEEKDAY_LABEL_WIDTH + MAX_WEEKS * CELL_WIDTH
+    }
+}
+
+impl<'a> Widget for CalendarHeatmap<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.draw_borders {
+            Block::default()
+                .style(Style::default().fg(Color::Red))
+                .borders(Borders::ALL)
+                .render(area, buf);
+        }
+
+        if area.height < CALENDAR_HEATMAP_HEIGHT || area.width <= WEEKDAY_LABEL_WIDTH {
+            return;
+        }
+
+        let counts = pomodoros_by_day(self.records);
+
+        let weeks = ((area.width - WEEKDAY_LABEL_WIDTH) / CELL_WIDTH).min(MAX_WEEKS);
+        if weeks == 0 {
+            return;
+        }
+
+        let last_monday =
+            self.last_day - Duration::days(self.last_day.weekday().num_days_from_monday() as i64);
+        let first_monday = last_monday - Duration::weeks(weeks as i64 - 1);
+
+        let label_style = Style::default().fg(Color::Gray);
+
+        for (row, label) in WEEKDAY_LABELS.iter().enumerate() {
+            buf.set_string(area.x, area.y + 1 + row as u16, label, label_style);
+        }
+
+        let mut previous_month = None;
+        let mut month_label_end = 0;
+
+        for week in 0..weeks {
+            let monday = first_monday + Duration::weeks(week as i64);
+            let x = area.x + WEEKDAY_LABEL_WIDTH + week * CELL_WIDTH;
+
+            if previous_month != Some(monday.month()) && x >= month_label_end {
+                let label = monday.format("%b").to_string();
+                if x + label.len() as u16 <= area.right() {
+                    buf.set_string(x, area.y, &label, label_style);
+                    month_label_end = x + label.len() as u16 + 1;
+                }
+            }
+            previous_month = Some(monday.month());
+
+            for weekday in 0..7 {
+                let day = monday + Duration::days(weekday as i64);
+                if day > self.last_day {
+                    break;
+                }
+
+                let count = counts.get(&day).copied().unwrap_or(0);
+                self.draw_cell(buf, x, area.y + 1 + weekday as u16, level(count));
+            }
+        }
+
+        // "Less", the cells and "More", unless they don't fit
+        let legend_width = 5 + LEVEL_COLORS.len() as u16 * CELL_WIDTH + 4;
+        if WEEKDAY_LABEL_WIDTH + legend_width <= area.width {
+            let legend_y = area.y + 8;
+            buf.set_string(area.x + WEEKDAY_LABEL_WIDTH, legend_y, "Less", label_style);
+            for level in 0..LEVEL_COLORS.len() {
+                let x = area.x + WEEKDAY_LABEL_WIDTH + 5 + level as u16 * CELL_WIDTH;
+                self.draw_cell(buf, x, legend_y, level);
+            }
+            buf.set_string(
+                area.x + WEEKDAY_LABEL_WIDTH + 5 + LEVEL_COLORS.len() as u16 * CELL_WIDTH,
+                legend_y,
+                "More",
+                label_style,
+            );
+        }
+
+        let (current, longest) = streaks(&counts, self.last_day);
+        let total: u64 = counts
+            .iter()
+            .filter(|(day, _)| **day >= first_monday && **day <= self.last_day)
+            .map(|(_, count)| count)
+            .sum();
+        let summary = format!(
+            "{} pomodoros in {} weeks | current streak: {} days | longest streak: {} days",
+            total, weeks, current, longest
+        );
+        buf.set_stringn(
+            area.x + WEEKDAY_LABEL_WIDTH,
+            area.y + 9,
+            summary,
+            (area.width - WEEKDAY_LABEL_WIDTH) as usize,
+            Style::default(),
+        );
+    }
+}
+
+impl<'a> CalendarHeatmap<'a> {
+    fn draw_cell(&self, buf: &mut Buffer, x: u16, y: u16, level: usize) {
+        match self.monochrome {
+            true => buf.set_string(x, y, LEVEL_SYMBOLS[level], Style::default()),
+            false => buf.set_string(x, y, CELL_SYMBOL, Style::default().fg(LEVEL_COLORS[level])),
+        }
+    }
+}
+
+fn level(count: u64) -> usize {
+    match count {
+        0 => 0,
+        1..=2 => 1,
+        3..=4 => 2,
+        5..=7 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::pomodoro_technique::Stage;
+    use crate::widgets::{lines, render};
+
+    fn pomodoros(day: NaiveDate, count: usize) -> Vec<CycleRecord> {
+        let started_at = Local
+            .from_local_datetime(&day.and_hms_opt(9, 0, 0).unwrap())
+            .unwrap();
+
+        vec![
+            CycleRecord {
+                stage: Stage::Work,
+                task: None,
+                started_at,
+                finished_at: started_at + Duration::minutes(25),
+                completed: true,
+                focused_secs: 25 * 60,
+                paused_secs: 0,
+                interruptions: Vec::new(),
+                commits: Vec::new(),
+            };
+            count
+        ]
+    }
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn lays_out_a_column_per_week_ending_on_the_last_day() {
+        // A Wednesday, in the third of the three weeks which fit
+        let last_day = day(5, 1);
+        let mut records = pomodoros(last_day, 3);
+        records.extend(pomodoros(day(4, 15), 1));
+
+        // Too narrow for the legend, which is left out
+        let buffer = render(
+            CalendarHeatmap::default()
+                .records(&records)
+                .last_day(last_day),
+            WEEKDAY_LABEL_WIDTH + 3 * CELL_WIDTH,
+            CALENDAR_HEATMAP_HEIGHT,
+        );
+        let lines = lines(&buffer);
+
+        assert!(lines[0].starts_with("    Apr"));
+        assert!(lines[1].starts_with("Mon"));
+        assert!(lines[7].starts_with("Sun"));
+
+        // Monday 2024-04-15 starts the first column, and the last day is on its weekday's row
+        assert_eq!(buffer.get(4, 1).fg, LEVEL_COLORS[1]);
+        assert_eq!(buffer.get(6, 1).fg, LEVEL_COLORS[0]);
+        assert_eq!(buffer.get(8, 3).fg, LEVEL_COLORS[2]);
+        assert_eq!(buffer.get(8, 3).symbol, CELL_SYMBOL);
+        // The days after it are left out
+        assert_eq!(buffer.get(8, 4).symbol, " ");
+    }
+
+    #[test]
+    fn sums_up_the_weeks_shown_and_the_streaks() {
+        let last_day = day(5, 1);
+        let mut records = pomodoros(day(4, 29), 2);
+        records.extend(pomodoros(day(4, 30), 1));
+        // Before the year shown, but part of the longest streak
+        for d in 1..=5 {
+            records.extend(pomodoros(NaiveDate::from_ymd_opt(2023, 3, d).unwrap(), 1));
+        }
+
+        let buffer = render(
+            CalendarHeatmap::default()
+                .records(&records)
+                .last_day(last_day)
+                .monochrome(true),
+            120,
+            CALENDAR_HEATMAP_HEIGHT,
+        );
+        let lines = lines(&buffer);
+
+        assert_eq!(
+            buffer.get(4 + 2 * (MAX_WEEKS - 1), 1).symbol,
+            LEVEL_SYMBOLS[1]
+        );
+        assert!(lines[9]
+            .contains("3 pomodoros in 53 weeks | current streak: 2 days | longest streak: 5 days"));
+    }
+
+    #[test]
+    fn levels_grow_with_the_pomodoros() {
+        let levels: Vec<usize> = [0, 1, 2, 3, 5, 7, 8, 20]
+            .iter()
+            .map(|c| level(*c))
+            .collect();
+        assert_eq!(levels, vec![0, 1, 1, 2, 3, 3, 4, 4]);
+    }
+}
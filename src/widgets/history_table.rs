@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+use crate::pomodoro_technique::{wall_clock, Cycle};
+
+const HEADER: [&str; 8] = [
+    "Stage",
+    "Start",
+    "End",
+    "Focused",
+    "Paused",
+    "Interruptions",
+    "Commits",
+    "Task",
+];
+const WIDTHS: [Constraint; 8] = [
+    Constraint::Length(11),
+    Constraint::Length(5),
+    Constraint::Length(5),
+    Constraint::Length(8),
+    Constraint::Length(8),
+    Constraint::Length(13),
+    Constraint::Length(7),
+    Constraint::Min(10),
+];
+
+/// Tabulates finished cycles, followed by a row with their totals.
+#[derive(Clone, Default)]
+pub struct HistoryTable<'a> {
+    cycles: Vec<&'a Cycle>,
+    selected: Option<usize>,
+    draw_borders: bool,
+}
+
+impl<'a> HistoryTable<'a> {
+    pub fn cycles(mut self, cycles: Vec<&'a Cycle>) -> HistoryTable<'a> {
+        self.cycles = cycles;
+        self
+    }
+
+    pub fn selected(mut self, selected: Option<usize>) -> HistoryTable<'a> {
+        self.selected = selected;
+        self
+    }
+
+    pub fn borders(mut self, draw_borders: bool) -> HistoryTable<'a> {
+        self.draw_borders = draw_borders;
+        self
+    }
+}
+
+impl<'a> Widget for HistoryTable<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        if self.draw_borders {
+            Block::default()
+                .style(Style::default().fg(Color::Red))
+                .borders(Borders::ALL)
+                .render(area, buf);
+        }
+
+        let mut rows: Vec<Vec<String>> = self
+            .cycles
+            .iter()
+            .map(|cycle| {
+                vec![
+                    cycle.stage.to_string(),
+                    format_time(cycle.started_at.map(wall_clock)),
+                    format_time(cycle.finished_at.map(wall_clock)),
+                    format_duration(cycle.focused_time()),
+                    format_duration(cycle.paused_time()),
+                    cycle.interruption_history.len().to_string(),
+                    cycle.commits.len().to_string(),
+                    cycle.task.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        let zero = Duration::new(0, 0);
+        rows.push(vec![
+            String::from("Total"),
+            String::new(),
+            String::new(),
+            format_duration(self.cycles.iter().fold(zero, |t, c| t + c.focused_time())),
+            format_duration(self.cycles.iter().fold(zero, |t, c| t + c.paused_time())),
+            self.cycles
+                .iter()
+                .map(|cycle| cycle.interruption_history.len())
+                .sum::<usize>()
+                .to_string(),
+            self.cycles
+                .iter()
+                .map(|cycle| cycle.commits.len())
+                .sum::<usize>()
+                .to_string(),
+            String::new(),
+        ]);
+
+        let totals_style = Style::default().add_modifier(Modifier::BOLD);
+        let last = rows.len() - 1;
+
+        let table = Table::new(
+            HEADER.iter(),
+            rows.into_iter().enumerate().map(|(idx, row)| match idx {
+                idx if idx == last => Row::StyledData(row.into_iter(), totals_style),
+                _ => Row::Data(row.into_iter()),
+            }),
+        )
+        .block(
+            Block::default()
+                .title("TODAY")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .header_style(Style::default().fg(Color::Yellow))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .widths(&WIDTHS)
+        .column_spacing(1);
+
+        let mut state = TableState::default();
+        state.select(self.selected);
+
+        StatefulWidget::render(table, area, buf, &mut state);
+    }
+}
+
+/// Formats a duration as `H:MM:SS`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+fn format_time(time: Option<chrono::DateTime<chrono::Local>>) -> String {
+    time.map_or(String::from("--:--"), |time| {
+        time.format("%H:%M").to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::pomodoro_technique::{Interruption, Stage};
+    use crate::widgets::{lines, render};
+
+    fn cycle(stage: Stage, task: Option<&str>) -> Cycle {
+        let finished_at = Instant::now();
+        let started_at = finished_at - Duration::from_secs(25 * 60);
+
+        let mut interruption = Interruption::new(started_at + Duration::from_secs(60));
+        interruption.finished_at = Some(started_at + Duration::from_secs(120));
+
+        let mut cycle = Cycle::new(0, stage);
+        cycle.task = task.map(String::from);
+        cycle.started_at = Some(started_at);
+        cycle.finished_at = Some(finished_at);
+        cycle.interruption_history.push(interruption);
+        cycle
+    }
+
+    fn row<'a>(lines: &'a [String], starting_with: &str) -> &'a str {
+        lines
+            .iter()
+            .find(|line| line.trim_start_matches('│').starts_with(starting_with))
+            .unwrap_or_else(|| panic!("no row starting with {:?} in {:#?}", starting_with, lines))
+    }
+
+    #[test]
+    fn renders_each_cycle_and_their_totals() {
+        let work = cycle(Stage::Work, Some("write tests"));
+        let short_break = cycle(Stage::ShortBreak, None);
+
+        let buffer = render(
+            HistoryTable::default().cycles(vec![&work, &short_break]),
+            80,
+            7,
+        );
+        let lines = lines(&buffer);
+
+        assert!(lines[0].contains("TODAY"));
+        assert!(row(&lines, "Stage").contains("Interruptions Commits Task"));
+
+        let work_row = row(&lines, "Work");
+        assert!(work_row.contains("0:24:00  0:01:00  1             0       write tests"));
+
+        let break_row = row(&lines, "Short break");
+        assert!(break_row.contains("0:24:00  0:01:00  1             0"));
+
+        let totals_row = row(&lines, "Total");
+        assert!(totals_row.contains("0:48:00  0:02:00  2             0"));
+        assert!(buffer.get(1, 5).modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn highlights_the_selected_cycle() {
+        let work = cycle(Stage::Work, None);
+        let long_break = cycle(Stage::LongBreak, None);
+
+        let buffer = render(
+            HistoryTable::default()
+                .cycles(vec![&work, &long_break])
+                .selected(Some(1)),
+            80,
+            7,
+        );
+
+        assert!(!buffer.get(1, 3).modifier.contains(Modifier::REVERSED));
+        assert!(buffer.get(1, 4).modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,128 @@
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget,
+    },
+};
+
+use crate::pomodoro_technique::Interruption;
+
+/// Lists the finished interruptions of a cycle, with their durations and annotations.
+#[derive(Clone, Default)]
+pub struct InterruptionsPanel<'a> {
+    interruptions: &'a [Interruption],
+    selected: Option<usize>,
+    draw_borders: bool,
+}
+
+impl<'a> InterruptionsPanel<'a> {
+    pub fn interruptions(mut self, interruptions: &'a [Interruption]) -> InterruptionsPanel<'a> {
+        self.interruptions = interruptions;
+        self
+    }
+
+    pub fn selected(mut self, selected: Option<usize>) -> InterruptionsPanel<'a> {
+        self.selected = selected;
+        self
+    }
+
+    pub fn borders(mut self, draw_borders: bool) -> InterruptionsPanel<'a> {
+        self.draw_borders = draw_borders;
+        self
+    }
+}
+
+impl<'a> Widget for InterruptionsPanel<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        if self.draw_borders {
+            Block::default()
+                .style(Style::default().fg(Color::Red))
+                .borders(Borders::ALL)
+                .render(area, buf);
+        }
+
+        let interruptions: Vec<ListItem> = self
+            .interruptions
+            .iter()
+            .map(|interruption| {
+                let duration = interruption.duration().as_secs();
+                let annotation = interruption
+                    .annotation
+                    .as_ref()
+                    .map_or(String::from(""), |annotation| annotation.to_string());
+
+                ListItem::new(Spans::from(vec![
+                    Span::from(duration.to_string()),
+                    Span::raw(" "),
+                    Span::from(annotation),
+                ]))
+            })
+            .collect();
+
+        let interruptions_list = List::new(interruptions)
+            .block(
+                Block::default()
+                    .title(Span::from("INTERRUPTIONS"))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::ITALIC));
+
+        let mut state = ListState::default();
+        state.select(self.selected);
+
+        StatefulWidget::render(interruptions_list, area, buf, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::widgets::{lines, render};
+
+    fn interruption(secs: u64, annotation: Option<&str>) -> Interruption {
+        let started_at = Instant::now();
+        let mut interruption = Interruption::new(started_at);
+        interruption.finished_at = Some(started_at + Duration::from_secs(secs));
+        interruption.annotation = annotation.map(String::from);
+        interruption
+    }
+
+    #[test]
+    fn lists_the_interruptions_with_the_selected_one_highlighted() {
+        let interruptions = [interruption(90, Some("phone call")), interruption(30, None)];
+
+        let buffer = render(
+            InterruptionsPanel::default()
+                .interruptions(&interruptions)
+                .selected(Some(1)),
+            24,
+            5,
+        );
+        let lines = lines(&buffer);
+
+        assert!(lines[0].contains("INTERRUPTIONS"));
+        assert_eq!(lines[1], "│90 phone call         │");
+        assert_eq!(lines[2], "│30                    │");
+        assert!(!buffer.get(1, 1).modifier.contains(Modifier::ITALIC));
+        assert!(buffer.get(1, 2).modifier.contains(Modifier::ITALIC));
+    }
+
+    #[test]
+    fn shows_an_empty_box_without_interruptions() {
+        let buffer = render(InterruptionsPanel::default(), 24, 4);
+        let lines = lines(&buffer);
+
+        assert!(lines[0].contains("INTERRUPTIONS"));
+        assert_eq!(lines[1], format!("│{}│", " ".repeat(22)));
+        assert_eq!(lines[2], format!("│{}│", " ".repeat(22)));
+        assert!(!buffer.get(1, 1).modifier.contains(Modifier::ITALIC));
+    }
+}
@@ -1,3 +1,41 @@
+mod annotation_popup;
+mod calendar_heatmap;
+mod history_table;
+mod interruptions_panel;
+mod pause_timer;
+mod productivity_charts;
+mod text_input;
 mod timer;
 
+pub use self::annotation_popup::AnnotationPopup;
+pub use self::calendar_heatmap::{CalendarHeatmap, CALENDAR_HEATMAP_HEIGHT};
+pub use self::history_table::{format_duration, HistoryTable};
+pub use self::interruptions_panel::InterruptionsPanel;
+pub use self::pause_timer::PauseTimer;
+pub use self::productivity_charts::{ProductivityCharts, CHARTS_WINDOW_DAYS};
+pub use self::text_input::{TextField, TextInput};
 pub use self::timer::Timer;
+
+/// Draws `widget` on a terminal of the given size, returning what ended up on it.
+#[cfg(test)]
+fn render<W: tui::widgets::Widget>(widget: W, width: u16, height: u16) -> tui::buffer::Buffer {
+    let mut terminal = tui::Terminal::new(tui::backend::TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|f| {
+            let area = f.size();
+            f.render_widget(widget, area);
+        })
+        .unwrap();
+
+    terminal.backend().buffer().clone()
+}
+
+/// The text on each line of `buffer`.
+#[cfg(test)]
+fn lines(buffer: &tui::buffer::Buffer) -> Vec<String> {
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol.as_str()).collect())
+        .collect()
+}
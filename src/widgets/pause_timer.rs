@@ -0,0 +1,104 @@
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+/// Displays for how long the timer has been paused,
+/// in yellow then red as the pause grows too long.
+#[derive(Clone, Default)]
+pub struct PauseTimer {
+    has_been_paused_for: Option<u64>,
+    thresholds: Option<(u64, u64)>,
+    draw_borders: bool,
+}
+
+impl PauseTimer {
+    pub fn has_been_paused_for(mut self, seconds: u64) -> PauseTimer {
+        self.has_been_paused_for = Some(seconds);
+        self
+    }
+
+    /// Seconds of pause after which to turn yellow and red.
+    pub fn thresholds(mut self, warn_after: u64, alert_after: u64) -> PauseTimer {
+        self.thresholds = Some((warn_after, alert_after));
+        self
+    }
+
+    pub fn borders(mut self, draw_borders: bool) -> PauseTimer {
+        self.draw_borders = draw_borders;
+        self
+    }
+}
+
+impl Widget for PauseTimer {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.draw_borders {
+            Block::default()
+                .style(Style::default().fg(Color::Red))
+                .borders(Borders::ALL)
+                .render(area, buf);
+        }
+
+        let has_been_paused_for = match self.has_been_paused_for {
+            Some(seconds) => seconds,
+            None => return,
+        };
+
+        let minutes = has_been_paused_for / 60;
+        let seconds = has_been_paused_for % 60;
+
+        let style = match self.thresholds {
+            Some((_, alert_after)) if has_been_paused_for >= alert_after => {
+                Style::default().fg(Color::Red)
+            }
+            Some((warn_after, _)) if has_been_paused_for >= warn_after => {
+                Style::default().fg(Color::Yellow)
+            }
+            _ => Style::default(),
+        };
+
+        Paragraph::new(Span::styled(
+            format!("{:02}:{:02}", minutes, seconds),
+            style,
+        ))
+        .alignment(Alignment::Center)
+        .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::{lines, render};
+
+    #[test]
+    fn renders_the_pause_centered() {
+        let buffer = render(PauseTimer::default().has_been_paused_for(75), 9, 1);
+
+        assert_eq!(lines(&buffer), vec!["  01:15  "]);
+    }
+
+    #[test]
+    fn renders_nothing_without_a_pause() {
+        let buffer = render(PauseTimer::default(), 9, 1);
+
+        assert_eq!(lines(&buffer), vec!["         "]);
+    }
+
+    #[test]
+    fn turns_yellow_then_red_as_the_pause_grows() {
+        let color = |seconds| {
+            let pause_timer = PauseTimer::default()
+                .has_been_paused_for(seconds)
+                .thresholds(60, 120);
+            render(pause_timer, 5, 1).get(0, 0).fg
+        };
+
+        assert_eq!(color(59), Color::Reset);
+        assert_eq!(color(60), Color::Yellow);
+        assert_eq!(color(120), Color::Red);
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{BarChart, Block, BorderType, Borders, Clear, Sparkline, Widget},
+};
+
+use crate::history::{
+    focused_minutes_by_hour, interruptions_per_pomodoro, pomodoros_per_day, CycleRecord,
+};
+
+/// How many days the charts cover at once.
+pub const CHARTS_WINDOW_DAYS: u32 = 14;
+
+/// Charts of the persisted history over a window of `CHARTS_WINDOW_DAYS` days:
+/// pomodoros per day, focused minutes by hour of the day and interruptions per pomodoro.
+#[derive(Clone)]
+pub struct ProductivityCharts<'a> {
+    records: &'a [CycleRecord],
+    last_day: NaiveDate,
+    draw_borders: bool,
+}
+
+impl<'a> Default for ProductivityCharts<'a> {
+    fn default() -> ProductivityCharts<'a> {
+        ProductivityCharts {
+            records: &[],
+            last_day: chrono::Local::now().date_naive(),
+            draw_borders: false,
+        }
+    }
+}
+
+impl<'a> ProductivityCharts<'a> {
+    pub fn records(mut self, records: &'a [CycleRecord]) -> ProductivityCharts<'a> {
+        self.records = records;
+        self
+    }
+
+    pub fn last_day(mut self, last_day: NaiveDate) -> ProductivityCharts<'a> {
+        self.last_day = last_day;
+        self
+    }
+
+    pub fn borders(mut self, draw_borders: bool) -> ProductivityCharts<'a> {
+        self.draw_borders = draw_borders;
+        self
+    }
+}
+
+impl<'a> Widget for ProductivityCharts<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+
+        let first_day = self.last_day - Duration::days(CHARTS_WINDOW_DAYS as i64 - 1);
+
+        let title = format!(
+            "{} - {} (←/→)",
+            first_day.format("%b %d"),
+            self.last_day.format("%b %d")
+        );
+        let outer = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let inner = outer.inner(area);
+        outer.render(area, buf);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(20),
+                ]
+                .as_ref(),
+            )
+            .split(inner);
+
+        if self.draw_borders {
+            for &chunk in chunks.iter() {
+                Block::default()
+                    .style(Style::default().fg(Color::Red))
+                    .borders(Borders::ALL)
+                    .render(chunk, buf);
+            }
+        }
+
+        let per_day = pomodoros_per_day(self.records, self.last_day, CHARTS_WINDOW_DAYS);
+        let day_labels: Vec<String> = per_day
+            .iter()
+            .map(|(day, _)| format!("{:02}", day.day()))
+            .collect();
+        let per_day: Vec<(&str, u64)> = day_labels
+            .iter()
+            .zip(per_day.iter())
+            .map(|(label, (_, count))| (label.as_str(), *count))
+            .collect();
+
+        BarChart::default()
+            .block(Block::default().title("Pomodoros per day"))
+            .data(&per_day)
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Red))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Red))
+            .render(chunks[0], buf);
+
+        let by_hour = focused_minutes_by_hour(self.records, first_day, self.last_day);
+        let hour_labels: Vec<String> = (0..24).map(|hour| format!("{:02}", hour)).collect();
+        let by_hour: Vec<(&str, u64)> = hour_labels
+            .iter()
+            .zip(by_hour.iter())
+            .map(|(label, minutes)| (label.as_str(), *minutes))
+            .collect();
+
+        BarChart::default()
+            .block(Block::default().title("Focused minutes by hour"))
+            .data(&by_hour)
+            .bar_width(2)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Green))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Green))
+            .render(chunks[1], buf);
+
+        // Only the latest pomodoros are shown when they don't all fit
+        let interruptions = interruptions_per_pomodoro(self.records, first_day, self.last_day);
+        let shown = interruptions.len().min(chunks[2].width as usize);
+        let interruptions = &interruptions[interruptions.len() - shown..];
+
+        Sparkline::default()
+            .block(Block::default().title("Interruptions per pomodoro"))
+            .data(interruptions)
+            .style(Style::default().fg(Color::Yellow))
+            .render(chunks[2], buf);
+    }
+}
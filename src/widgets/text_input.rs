@@ -0,0 +1,298 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// An editable line of text with a movable cursor.
+///
+/// Besides the obvious keys, it understands a few readline-style shortcuts:
+/// Ctrl+A/Ctrl+E move to the start/end of the line, Ctrl+W deletes the word
+/// before the cursor, Ctrl+U deletes everything before the cursor and Ctrl+Y
+/// pastes back whatever the last of those deleted.
+#[derive(Clone, Default)]
+pub struct TextInput {
+    value: String,
+    /// Byte offset of the cursor into `value`; always on a char boundary.
+    cursor: usize,
+    kill_buffer: String,
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Replaces the whole value, leaving the cursor at its end.
+    pub fn set_value(&mut self, value: &str) {
+        self.value.clear();
+        self.cursor = 0;
+        self.paste(value);
+    }
+
+    pub fn insert(&mut self, c: char) {
+        if c.is_control() {
+            return;
+        }
+
+        self.value.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Inserts `text` at the cursor. Line breaks become spaces and other
+    /// control characters are dropped, since the input is a single line.
+    pub fn paste(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' | '\r' | '\t' => self.insert(' '),
+                c => self.insert(c),
+            }
+        }
+    }
+
+    pub fn delete_before_cursor(&mut self) {
+        if let Some(c) = self.value[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.value.remove(self.cursor);
+        }
+    }
+
+    pub fn delete_at_cursor(&mut self) {
+        if self.cursor < self.value.len() {
+            self.value.remove(self.cursor);
+        }
+    }
+
+    pub fn delete_word_before_cursor(&mut self) {
+        let start = self.previous_word_boundary();
+        self.kill_buffer = self.value.drain(start..self.cursor).collect();
+        self.cursor = start;
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.kill_buffer = self.value.drain(..self.cursor).collect();
+        self.cursor = 0;
+    }
+
+    pub fn yank(&mut self) {
+        let killed = self.kill_buffer.clone();
+        self.paste(&killed);
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(c) = self.value[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(c) = self.value[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.previous_word_boundary();
+    }
+
+    pub fn move_word_right(&mut self) {
+        let rest = &self.value[self.cursor..];
+        let word_start = rest.len() - rest.trim_start().len();
+        let word_length = rest[word_start..]
+            .find(char::is_whitespace)
+            .unwrap_or(rest.len() - word_start);
+        self.cursor += word_start + word_length;
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.len();
+    }
+
+    /// Applies a key press to the input.
+    /// Returns whether the key was meant for the input at all.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.move_home(),
+            KeyCode::Char('e') if ctrl => self.move_end(),
+            KeyCode::Char('w') if ctrl => self.delete_word_before_cursor(),
+            KeyCode::Char('u') if ctrl => self.delete_to_start(),
+            KeyCode::Char('y') if ctrl => self.yank(),
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.delete_before_cursor(),
+            KeyCode::Delete => self.delete_at_cursor(),
+            KeyCode::Left if ctrl => self.move_word_left(),
+            KeyCode::Right if ctrl => self.move_word_right(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Returns the part of the value that fits in `width` columns while
+    /// keeping the cursor in sight, along with the cursor column within it.
+    pub fn visible(&self, width: u16) -> (&str, u16) {
+        let width = width as usize;
+
+        if width == 0 {
+            return ("", 0);
+        }
+
+        // The cursor takes a column of its own when it sits past the last char
+        let mut start = self.cursor;
+        let mut before_cursor = 0;
+        for c in self.value[..self.cursor].chars().rev() {
+            let c_width = c.width().unwrap_or(0);
+            if before_cursor + c_width >= width {
+                break;
+            }
+            before_cursor += c_width;
+            start -= c.len_utf8();
+        }
+
+        let mut end = start;
+        let mut visible_width = 0;
+        for c in self.value[start..].chars() {
+            let c_width = c.width().unwrap_or(0);
+            if visible_width + c_width > width {
+                break;
+            }
+            visible_width += c_width;
+            end += c.len_utf8();
+        }
+
+        (&self.value[start..end], before_cursor as u16)
+    }
+
+    fn previous_word_boundary(&self) -> usize {
+        let before = self.value[..self.cursor].trim_end();
+        before.rfind(char::is_whitespace).map_or(0, |idx| {
+            idx + before[idx..].chars().next().unwrap().len_utf8()
+        })
+    }
+}
+
+/// Renders a `TextInput` on the first line of its area.
+#[derive(Clone, Default)]
+pub struct TextField<'a> {
+    input: Option<&'a TextInput>,
+}
+
+impl<'a> TextField<'a> {
+    pub fn input(mut self, input: &'a TextInput) -> TextField<'a> {
+        self.input = Some(input);
+        self
+    }
+
+    /// Where the terminal cursor should be placed when the field is rendered in `area`.
+    pub fn cursor(&self, area: Rect) -> (u16, u16) {
+        let column = self.input.map_or(0, |input| input.visible(area.width).1);
+        (area.x + column, area.y)
+    }
+}
+
+impl<'a> Widget for TextField<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let input = match self.input {
+            Some(input) if area.height > 0 => input,
+            _ => return,
+        };
+
+        let (visible, _) = input.visible(area.width);
+        buf.set_stringn(area.x, area.y, visible, visible.width(), Style::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: &str) -> TextInput {
+        let mut input = TextInput::default();
+        input.set_value(value);
+        input
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn edits_around_multibyte_chars() {
+        let mut input = input("café");
+        input.move_left();
+        input.insert('ñ');
+        assert_eq!(input.value(), "cafñé");
+
+        input.move_right();
+        input.delete_before_cursor();
+        input.move_home();
+        input.delete_at_cursor();
+        assert_eq!(input.value(), "afñ");
+
+        input.move_end();
+        input.insert('🍅');
+        assert_eq!(input.value(), "afñ🍅");
+        assert_eq!(input.cursor, input.value().len());
+    }
+
+    #[test]
+    fn kills_and_yanks_like_readline() {
+        let mut input = input("call  back later");
+        assert!(input.handle_key(ctrl('w')));
+        assert_eq!(input.value(), "call  back ");
+
+        input.handle_key(ctrl('w'));
+        assert_eq!(input.value(), "call  ");
+        input.move_home();
+        input.handle_key(ctrl('y'));
+        assert_eq!(input.value(), "back call  ");
+
+        input.handle_key(ctrl('e'));
+        input.handle_key(ctrl('u'));
+        assert!(input.is_empty());
+        input.handle_key(ctrl('y'));
+        assert_eq!(input.value(), "back call  ");
+
+        // Other shortcuts are left to the view
+        assert!(!input.handle_key(ctrl('c')));
+    }
+
+    #[test]
+    fn moves_by_words() {
+        let mut input = input("phone  call from Ada");
+        input.move_home();
+        input.move_word_right();
+        input.move_word_right();
+        assert_eq!(input.cursor, "phone  call".len());
+
+        input.move_word_left();
+        assert_eq!(input.cursor, "phone  ".len());
+    }
+
+    #[test]
+    fn scrolls_to_keep_the_cursor_in_sight() {
+        let mut input = input("a long annotation");
+        assert_eq!(input.visible(8), ("otation", 7));
+
+        input.move_home();
+        assert_eq!(input.visible(8), ("a long a", 0));
+
+        // Wide chars take two columns, and are never cut in half
+        let input = self::input("🍅🍅🍅🍅");
+        assert_eq!(input.visible(5), ("🍅🍅", 4));
+        assert_eq!(input.visible(0), ("", 0));
+    }
+}
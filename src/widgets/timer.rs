@@ -104,6 +104,7 @@ pub struct Timer<'a> {
     draw_borders: bool,
     is_due: bool,
     is_paused: bool,
+    is_ending: bool,
     has_been_paused_for: Option<u64>,
 }
 
@@ -114,6 +115,7 @@ impl<'a> Default for Timer<'a> {
             draw_borders: false,
             is_due: false,
             is_paused: false,
+            is_ending: false,
             has_been_paused_for: None,
         }
     }
@@ -135,6 +137,12 @@ impl<'a> Timer<'a> {
         self
     }
 
+    /// Highlights a stage which is about to end.
+    pub fn ending(mut self, is_ending: bool) -> Timer<'a> {
+        self.is_ending = is_ending;
+        self
+    }
+
     pub fn borders(mut self, draw_borders: bool) -> Timer<'a> {
         self.draw_borders = draw_borders;
         self
@@ -185,9 +193,10 @@ impl<'a> Widget for Timer<'a> {
                     draw_borders(&area, buf);
                 }
 
-                let style = match self.is_due || self.is_paused {
-                    true => Style::default().fg(Color::Red),
-                    false => Style::default().fg(Color::Gray),
+                let style = match (self.is_due || self.is_paused, self.is_ending) {
+                    (true, _) => Style::default().fg(Color::Red),
+                    (false, true) => Style::default().fg(Color::Yellow),
+                    (false, false) => Style::default().fg(Color::Gray),
                 };
 
                 let can_draw_grapheme =